@@ -39,9 +39,10 @@ fn main() {
     decorator.set_margins(10);
     decorator.set_header(|page| {
         let mut layout = elements::LinearLayout::vertical();
-        if page > 1 {
+        if page.number > 1 {
             layout.push(
-                elements::Paragraph::new(format!("Page {}", page)).aligned(Alignment::Center),
+                elements::Paragraph::new(format!("Page {}", page.number))
+                    .aligned(Alignment::Center),
             );
             layout.push(elements::Break::new(1.));
         }
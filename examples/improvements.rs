@@ -15,8 +15,6 @@ use std::env;
 use numaelis_rckive_genpdf::Alignment;
 use numaelis_rckive_genpdf::Element as _;
 use numaelis_rckive_genpdf::{elements, fonts, style};
-use numaelis_rckive_genpdf::Margins;
-use numaelis_rckive_genpdf::Position;
 use numaelis_rckive_genpdf::style::BackgroundStyle;
 
 
@@ -86,15 +84,19 @@ fn main() {
     decorator.set_margins(10);
     decorator.set_header(|page| {
         let mut layout = elements::LinearLayout::vertical();
-        if page > 1 {
+        if page.number > 1 {
+            let total = page.total.unwrap_or_default();
             layout.push(
-                elements::Paragraph::new(format!("Page {}", page)).aligned(Alignment::Center),
+                elements::Paragraph::new(format!("Page {} of {}", page.number, total))
+                    .aligned(Alignment::Center),
             );
             layout.push(elements::Break::new(1.));
         }
         layout.styled(style::Style::new().with_font_size(10))
     });
     doc.set_page_decorator(decorator);
+    // Count the pages before rendering so that the header can print the total page count.
+    doc.set_two_pass_rendering(true);
     
     let line_style = style::LineStyle::new().with_thickness(0.2).with_color(style::Color::Greyscale(125));
     
@@ -116,28 +118,9 @@ fn main() {
     
     let mut decorator = numaelis_rckive_genpdf::SimplePageDecorator::new();
     
-    //Currently, the footer is created manually using layout orphan.
-        
-    let x = 0;
-    //A4 height = 297.0;
-    //A4 widht = 210.0;
-    let mut y = 297.0 - 10.0;
-    let width_area =  210.0 - 10.0 - 10.0;  
-    
-    let mut paragraph_footer = elements::Paragraph::new("footer paragraph")
-                            .aligned(Alignment::Center);
-                            
-    let hp = paragraph_footer.get_height(doc.context(), width_area);
-    let hp: f32 = hp.into();
-    y -= hp;
-    decorator.set_margins(Margins::trbl(10,10,hp+10.0,10));
-    decorator.set_footer(move |page| {
-         let mut layout = elements::LinearLayout::vertical().with_orphan(true);
-            
-        layout.set_orphan_position(x, y);
-        let paragraph_footer = paragraph_footer.clone();
-        layout.push(paragraph_footer);
-        layout
+    decorator.set_margins(10);
+    decorator.set_footer(|_| {
+        elements::Paragraph::new("footer paragraph").aligned(Alignment::Center)
     });
     doc.set_page_decorator(decorator);
     
@@ -146,8 +129,6 @@ fn main() {
     doc.set_page_frame_line_style(line_style.clone());
     doc.set_header_frame_line_style(line_style.clone());
     
-    // footer frame Manual
-    doc.set_rec_footer(Position::new(x,y), Position::new(width_area, hp));
     doc.set_footer_frame_line_style(line_style.clone());
     
     doc.push(elements::PageBreak::new());
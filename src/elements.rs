@@ -6,25 +6,36 @@
 //! It includes the following elements:
 //! - Containers:
 //!   - [`LinearLayout`][]: arranges its elements sequentially
+//!   - [`Columns`][]: arranges its elements in columns, continuing from one column to the next
 //!   - [`TableLayout`][]: arranges its elements in columns and rows
 //!   - [`OrderedList`][] and [`UnorderedList`][]: arrange their elements sequentially with bullet
 //!     points
 //! - Text:
 //!   - [`Text`][]: a single line of text
 //!   - [`Paragraph`][]: a wrapped and aligned paragraph of text
+//!   - [`Heading`][]: a paragraph that is added to the outline of the document
+//!   - [`TableOfContents`][]: a list of the headings of the document with their page numbers
 //! - Wrappers:
 //!   - [`FramedElement`][]: draws a frame around the wrapped element
 //!   - [`PaddedElement`][]: adds a padding to the wrapped element
 //!   - [`StyledElement`][]: sets a default style for the wrapped element and its children
+//!   - [`LinkedElement`][]: turns the wrapped element into a clickable link
+//!   - [`AnchoredElement`][]: adds a named destination at the wrapped element
+//!   - [`KeepTogether`][]: moves the wrapped element to the next page if it does not fit on the
+//!     current page
+//!   - [`Float`][]: places the wrapped element at the edge of the area so that the following text
+//!     flows around it
 //! - Other:
 //!   - [`Image`][]: an image (requires the `images` feature)
 //!   - [`Break`][]: adds forced line breaks as a spacer
 //!   - [`PageBreak`][]: adds a forced page break
+//!   - [`Watermark`][]: a rotated, semi-transparent text or image across the page
 //!
 //! You can create custom elements by implementing the [`Element`][] trait.
 //!
 //! [`Element`]: ../trait.Element.html
 //! [`LinearLayout`]: struct.LinearLayout.html
+//! [`Columns`]: struct.Columns.html
 //! [`TableLayout`]: struct.TableLayout.html
 //! [`OrderedList`]: struct.OrderedList.html
 //! [`UnorderedList`]: struct.UnorderedList.html
@@ -33,23 +44,32 @@
 //! [`Break`]: struct.Break.html
 //! [`PageBreak`]: struct.PageBreak.html
 //! [`Paragraph`]: struct.Paragraph.html
+//! [`Heading`]: struct.Heading.html
+//! [`TableOfContents`]: struct.TableOfContents.html
 //! [`FramedElement`]: struct.FramedElement.html
 //! [`PaddedElement`]: struct.PaddedElement.html
 //! [`StyledElement`]: struct.StyledElement.html
+//! [`LinkedElement`]: struct.LinkedElement.html
+//! [`AnchoredElement`]: struct.AnchoredElement.html
+//! [`KeepTogether`]: struct.KeepTogether.html
+//! [`Float`]: struct.Float.html
+//! [`Watermark`]: struct.Watermark.html
 
 #[cfg(feature = "images")]
 mod images;
 
 use std::collections;
 use std::iter;
-use std::mem;
 
 use crate::error::{Error, ErrorKind};
 use crate::fonts;
 use crate::render;
-use crate::style::{LineStyle, Style, StyledString, BackgroundStyle};
+use crate::style::{BackgroundStyle, Color, LineStyle, Link, Style, StyledCow, StyledString};
 use crate::wrap;
-use crate::{Alignment, Context, Element, Margins, Mm, Position, RenderResult, Size};
+use crate::{
+    Alignment, Context, Direction, Element, HeadingInfo, LineBreaking, Margins, Mm, Position,
+    RenderResult, Rotation, Size, VerticalAlignment,
+};
 
 #[cfg(feature = "images")]
 pub use images::Image;
@@ -72,9 +92,49 @@ impl IntoBoxedElement for Box<dyn Element> {
     }
 }
 
+/// The width of an element in a horizontal [`LinearLayout`][].
+///
+/// The default width is a weight of one.
+///
+/// [`LinearLayout`]: struct.LinearLayout.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnWidth {
+    /// A fixed width.
+    Fixed(Mm),
+    /// A share of the width that is left after all fixed and content widths have been assigned,
+    /// relative to the weights of the other weighted elements.
+    Weight(usize),
+    /// The width of the content of the element.
+    ///
+    /// The width is measured with [`Element::measure`][] before the layout is rendered.
+    ///
+    /// [`Element::measure`]: ../trait.Element.html#method.measure
+    Content,
+}
+
+impl Default for ColumnWidth {
+    fn default() -> ColumnWidth {
+        ColumnWidth::Weight(1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Orientation {
+    Vertical,
+    Horizontal,
+}
+
 /// Arranges a list of elements sequentially.
 ///
-/// Currently, elements can only be arranged vertically.
+/// The elements can be arranged vertically ([`vertical`][]) or horizontally
+/// ([`horizontal`][]).  In a horizontal layout, the width of each element is determined by its
+/// [`ColumnWidth`][], and the elements can be aligned vertically using
+/// [`set_vertical_alignment`][].  If one of the elements of a horizontal layout does not fit on
+/// the current page, the remaining elements are continued on the next page.
+///
+/// In a vertical layout, an element can be kept on the same page as the beginning of the next
+/// element, for example to avoid a heading at the bottom of a page, see
+/// [`push_keep_with_next`][].
 ///
 /// # Examples
 ///
@@ -94,32 +154,78 @@ impl IntoBoxedElement for Box<dyn Element> {
 ///     .element(elements::Paragraph::new("Test2"));
 /// ```
 ///
+/// Horizontal:
+/// ```
+/// use rckive_genpdf::{elements, Mm, VerticalAlignment};
+/// use elements::ColumnWidth;
+/// let layout = elements::LinearLayout::horizontal()
+///     .element_with_width(elements::Text::new("Logo"), ColumnWidth::Fixed(Mm::from(40)))
+///     .element_with_width(elements::Paragraph::new("Address"), ColumnWidth::Weight(1))
+///     .element_with_width(elements::Text::new("Date"), ColumnWidth::Content)
+///     .with_vertical_alignment(VerticalAlignment::Center);
+/// ```
+///
+/// [`vertical`]: #method.vertical
+/// [`horizontal`]: #method.horizontal
+/// [`set_vertical_alignment`]: #method.set_vertical_alignment
+/// [`push_keep_with_next`]: #method.push_keep_with_next
+/// [`ColumnWidth`]: enum.ColumnWidth.html
 pub struct LinearLayout {
     elements: Vec<Box<dyn Element>>,
     render_idx: usize,
+    keep_with_next: Vec<bool>,
+    // Whether the element at render_idx has already been checked for the keep-with-next option
+    is_kept: bool,
     /// leave out
     orphan: bool,
     orphan_position: Position,
+    orientation: Orientation,
+    widths: Vec<ColumnWidth>,
+    vertical_alignment: VerticalAlignment,
+    column_widths: Vec<Mm>,
+    finished: Vec<bool>,
+    // Whether a part of the content has been rendered since the last reset
+    is_started: bool,
+    // The sizes of the areas that the element at render_idx has been rendered into
+    partial_sizes: Vec<Size>,
 }
 
 impl LinearLayout {
-    fn new() -> LinearLayout {
+    fn new(orientation: Orientation) -> LinearLayout {
         LinearLayout {
             elements: Vec::new(),
             render_idx: 0,
+            keep_with_next: Vec::new(),
+            is_kept: false,
             orphan: false, 
             orphan_position: Position::default(),
+            orientation,
+            widths: Vec::new(),
+            vertical_alignment: VerticalAlignment::default(),
+            column_widths: Vec::new(),
+            finished: Vec::new(),
+            is_started: false,
+            partial_sizes: Vec::new(),
         }
     }
 
     /// Creates a new linear layout that arranges its elements vertically.
     pub fn vertical() -> LinearLayout {
-        LinearLayout::new()
+        LinearLayout::new(Orientation::Vertical)
+    }
+
+    /// Creates a new linear layout that arranges its elements horizontally.
+    pub fn horizontal() -> LinearLayout {
+        LinearLayout::new(Orientation::Horizontal)
     }
 
     /// Adds the given element to this layout.
+    ///
+    /// In a horizontal layout, the element gets the default [`ColumnWidth`][].
+    ///
+    /// [`ColumnWidth`]: enum.ColumnWidth.html
     pub fn push<E: IntoBoxedElement>(&mut self, element: E) {
-        self.elements.push(element.into_boxed_element());
+        self.push_with_width(element, ColumnWidth::default());
     }
 
     /// Adds the given element to this layout and it returns the layout.
@@ -127,26 +233,88 @@ impl LinearLayout {
         self.push(element);
         self
     }
+
+    /// Adds the given element with the given width to this layout.
+    ///
+    /// The width is only used in horizontal layouts.
+    pub fn push_with_width<E: IntoBoxedElement>(&mut self, element: E, width: ColumnWidth) {
+        self.elements.push(element.into_boxed_element());
+        self.widths.push(width);
+        self.keep_with_next.push(false);
+    }
+
+    /// Adds the given element with the given width to this layout and returns the layout.
+    pub fn element_with_width<E: IntoBoxedElement>(
+        mut self,
+        element: E,
+        width: ColumnWidth,
+    ) -> Self {
+        self.push_with_width(element, width);
+        self
+    }
+
+    /// Adds the given element to this layout and keeps it on the same page as the beginning of the
+    /// next element.
+    ///
+    /// If the element and the first part of the next element do not fit on the current page, the
+    /// element is moved to the next page.  If the next element is kept with its next element too,
+    /// the whole chain is kept together.  To check whether the elements fit, they are rendered
+    /// into a measuring area first, so they have to support [`Element::reset`][].
+    ///
+    /// This option is only used in vertical layouts.
+    ///
+    /// [`Element::reset`]: ../trait.Element.html#method.reset
+    pub fn push_keep_with_next<E: IntoBoxedElement>(&mut self, element: E) {
+        self.push(element);
+        if let Some(keep_with_next) = self.keep_with_next.last_mut() {
+            *keep_with_next = true;
+        }
+    }
+
+    /// Adds the given element to this layout, keeps it on the same page as the beginning of the
+    /// next element and returns the layout.
+    pub fn element_keep_with_next<E: IntoBoxedElement>(mut self, element: E) -> Self {
+        self.push_keep_with_next(element);
+        self
+    }
+
+    /// Sets the vertical alignment of the elements of a horizontal layout.
+    ///
+    /// The alignment is only applied on the first page.  If the layout is continued on another
+    /// page, the remaining content is top-flushed.
+    pub fn set_vertical_alignment(&mut self, alignment: VerticalAlignment) {
+        self.vertical_alignment = alignment;
+    }
+
+    /// Sets the vertical alignment of the elements of a horizontal layout and returns the layout.
+    pub fn with_vertical_alignment(mut self, alignment: VerticalAlignment) -> Self {
+        self.set_vertical_alignment(alignment);
+        self
+    }
     
     /// Sets the orphan
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn set_orphan(&mut self, orphan: bool) {
         self.orphan = orphan;
     }
 
     /// Sets the orphan of this layout and returns the text.
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn with_orphan(mut self, orphan: bool) -> Self {
-        self.set_orphan(orphan);
+        self.orphan = orphan;
         self
     }
     
     /// Sets the position
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn set_orphan_position(&mut self, x: impl Into<Mm>, y: impl Into<Mm>) {
         self.orphan_position = Position::new(x,y);
     }
 
     /// Sets the position of this layout and returns the text.
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn with_position(mut self, x: impl Into<Mm>, y: impl Into<Mm>) -> Self {
-        self.set_orphan_position(x,y);
+        self.orphan_position = Position::new(x,y);
         self
     }
     
@@ -167,15 +335,26 @@ impl LinearLayout {
         let mut result = RenderResult::default();
         if !self.orphan  {
             while area.size().height > Mm(0.0) && self.render_idx < self.elements.len() {
+                if !self.is_kept && self.keep_with_next[self.render_idx] {
+                    self.is_kept = true;
+                    if self.should_move_to_next_page(context, &area, style)? {
+                        result.has_more = true;
+                        return Ok(result);
+                    }
+                }
+                let element_size = area.size();
                 let element_result =
                     self.elements[self.render_idx].render(context, area.clone(), style)?;
-                area.add_offset(Position::new(0, element_result.size.height));
+                area.add_content_offset(element_result.size.height);
                 result.size = result.size.stack_vertical(element_result.size);
                 if element_result.has_more {
+                    self.partial_sizes.push(element_size);
                     result.has_more = true;
                     return Ok(result);
                 }
                 self.render_idx += 1;
+                self.is_kept = false;
+                self.partial_sizes.clear();
             }
         }else{            
             let mut new_area = area.clone();            
@@ -191,6 +370,220 @@ impl LinearLayout {
         result.has_more = self.render_idx < self.elements.len();
         Ok(result)
     }
+
+    /// Returns whether the element at the render index and the elements it is kept with should be
+    /// moved to the next page because they do not fit into the given area, but on an empty page.
+    fn should_move_to_next_page(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<bool, Error> {
+        if area.is_at_top() || self.fits_with_next(context, area.clone(), style)? {
+            return Ok(false);
+        }
+        let page_area = render::Area::measuring(Size::new(area.size().width, area.empty_height()));
+        self.fits_with_next(context, page_area, style)
+    }
+
+    /// Returns whether the element at the render index, the elements it is kept with and the first
+    /// part of the last of these elements fit into the given area.
+    fn fits_with_next(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<bool, Error> {
+        let mut fits = true;
+        for idx in self.render_idx..self.elements.len() {
+            let result = self.elements[idx].measure(context, area.clone(), style)?;
+            if !self.keep_with_next[idx] {
+                fits = result.size != Size::new(0, 0) || !result.has_more;
+                break;
+            } else if result.has_more {
+                fits = false;
+                break;
+            }
+            area.add_content_offset(result.size.height);
+        }
+        Ok(fits)
+    }
+
+    /// Returns the rendering state of this vertical layout.
+    fn save_state(&self) -> LayoutState {
+        LayoutState {
+            render_idx: self.render_idx,
+            is_kept: self.is_kept,
+            is_started: self.is_started,
+            partial_sizes: self.partial_sizes.clone(),
+        }
+    }
+
+    /// Restores the given rendering state of this vertical layout after it has been rendered into
+    /// measuring areas.
+    ///
+    /// The elements that have not been rendered completely are reset, and the element at the
+    /// render index is rendered into measuring areas with the sizes of the areas that it has been
+    /// rendered into, so only this element is rendered again.
+    fn restore_state(
+        &mut self,
+        context: &Context,
+        state: &LayoutState,
+        style: Style,
+    ) -> Result<(), Error> {
+        for element in self.elements.iter_mut().skip(state.render_idx) {
+            element.reset();
+        }
+        self.render_idx = state.render_idx;
+        self.is_kept = state.is_kept;
+        self.is_started = state.is_started;
+        self.partial_sizes = state.partial_sizes.clone();
+        if let Some(element) = self.elements.get_mut(self.render_idx) {
+            for size in &state.partial_sizes {
+                element.render(context, render::Area::measuring(*size), style)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Calculates the widths of the elements of a horizontal layout.
+    fn measure_widths(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<Vec<Mm>, Error> {
+        // Fixed widths are clamped to the remaining width so that the elements stay in the area.
+        let mut remaining = area.size().width.max(Mm(0.0));
+        let mut widths = vec![Mm(0.0); self.elements.len()];
+        for (i, width) in self.widths.iter().enumerate() {
+            if let ColumnWidth::Fixed(width) = width {
+                widths[i] = width.max(Mm(0.0)).min(remaining);
+                remaining -= widths[i];
+            }
+        }
+
+        for (i, width) in self.widths.iter().enumerate() {
+            match width {
+                ColumnWidth::Fixed(_) => {}
+                ColumnWidth::Content => {
+                    let available = Size::new(remaining, area.size().height);
+                    let result = self.elements[i].measure(
+                        context,
+                        render::Area::measuring(available),
+                        style,
+                    )?;
+                    widths[i] = result.size.width.min(remaining);
+                    remaining -= widths[i];
+                }
+                ColumnWidth::Weight(_) => {}
+            }
+        }
+
+        let total_weight: usize = self
+            .widths
+            .iter()
+            .map(|width| match width {
+                ColumnWidth::Weight(weight) => *weight,
+                _ => 0,
+            })
+            .sum();
+        if total_weight > 0 {
+            let factor = remaining / total_weight as f32;
+            for (i, width) in self.widths.iter().enumerate() {
+                if let ColumnWidth::Weight(weight) = width {
+                    widths[i] = factor * *weight as f32;
+                }
+            }
+        }
+        Ok(widths)
+    }
+
+    /// Calculates the vertical offsets of the elements of a horizontal layout according to the
+    /// vertical alignment.
+    fn measure_offsets(
+        &mut self,
+        context: &Context,
+        areas: &[render::Area<'_>],
+        style: Style,
+    ) -> Result<Vec<Mm>, Error> {
+        let mut heights = Vec::with_capacity(areas.len());
+        for (area, element) in areas.iter().zip(self.elements.iter_mut()) {
+            heights.push(element.measure(context, area.clone(), style)?.size.height);
+        }
+        let max_height = heights.iter().fold(Mm(0.0), |max, height| max.max(*height));
+        Ok(heights
+            .into_iter()
+            .map(|height| match self.vertical_alignment {
+                VerticalAlignment::Top => Mm(0.0),
+                VerticalAlignment::Center => (max_height - height) / 2.0,
+                VerticalAlignment::Bottom => max_height - height,
+            })
+            .collect())
+    }
+
+    fn render_horizontal(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.elements.is_empty() {
+            return Ok(result);
+        }
+        if self.orphan {
+            area.add_offset(self.orphan_position);
+        }
+
+        let is_first = self.finished.is_empty();
+        if is_first {
+            self.column_widths = self.measure_widths(context, &area, style)?;
+            self.finished = vec![false; self.elements.len()];
+        }
+
+        let mut areas = Vec::with_capacity(self.elements.len());
+        let mut offset = Mm(0.0);
+        for width in &self.column_widths {
+            let mut column_area = area.clone();
+            column_area.add_offset(Position::new(offset, 0));
+            column_area.set_width(*width);
+            areas.push(column_area);
+            offset += *width;
+        }
+
+        let offsets = if is_first && self.vertical_alignment != VerticalAlignment::Top {
+            self.measure_offsets(context, &areas, style)?
+        } else {
+            vec![Mm(0.0); areas.len()]
+        };
+
+        let mut height = Mm(0.0);
+        for (i, mut column_area) in areas.into_iter().enumerate() {
+            if self.finished[i] {
+                continue;
+            }
+            column_area.add_offset(Position::new(0, offsets[i]));
+            let element_result = self.elements[i].render(context, column_area, style)?;
+            height = height.max(element_result.size.height + offsets[i]);
+            if !element_result.has_more {
+                self.finished[i] = true;
+                self.render_idx += 1;
+            }
+        }
+
+        if self.orphan {
+            // Like in a vertical layout, the elements of an orphan layout are rendered once at
+            // its position without using space in the area.
+            self.render_idx = self.elements.len();
+            return Ok(result);
+        }
+        if height > Mm(0.0) {
+            result.size = Size::new(offset, height);
+        }
+        result.has_more = self.render_idx < self.elements.len();
+        Ok(result)
+    }
 }
 
 impl Element for LinearLayout {
@@ -200,15 +593,345 @@ impl Element for LinearLayout {
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        // TODO: add horizontal layout
-        self.render_vertical(context, area, style)
+        let result = match self.orientation {
+            Orientation::Vertical => self.render_vertical(context, area, style)?,
+            Orientation::Horizontal => self.render_horizontal(context, area, style)?,
+        };
+        if result.size != Size::new(0, 0) {
+            self.is_started = true;
+        }
+        Ok(result)
+    }
+
+    fn reset(&mut self) {
+        self.render_idx = 0;
+        self.is_kept = false;
+        self.column_widths.clear();
+        self.finished.clear();
+        self.is_started = false;
+        self.partial_sizes.clear();
+        for element in &mut self.elements {
+            element.reset();
+        }
+    }
+
+    /// Measures this layout.
+    ///
+    /// A vertical layout restores its rendering state after measuring, so it can also be measured
+    /// after a part of it has been rendered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a part of a horizontal layout has already been rendered.
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        match self.orientation {
+            Orientation::Vertical => {
+                let state = self.save_state();
+                let result = self.render(context, area.to_measuring(), style);
+                self.restore_state(context, &state, style)?;
+                result
+            }
+            Orientation::Horizontal if self.is_started => Err(Error::new(
+                "A horizontal layout cannot be measured after it has been rendered partially",
+                ErrorKind::Internal,
+            )),
+            Orientation::Horizontal => crate::measure_and_reset(self, context, area, style),
+        }
     }
 }
 
 impl<E: IntoBoxedElement> iter::Extend<E> for LinearLayout {
     fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
-        self.elements
-            .extend(iter.into_iter().map(|e| e.into_boxed_element()))
+        for element in iter {
+            self.push(element);
+        }
+    }
+}
+
+/// The rendering state of a vertical [`LinearLayout`][], see [`LinearLayout::save_state`][].
+///
+/// [`LinearLayout`]: struct.LinearLayout.html
+/// [`LinearLayout::save_state`]: struct.LinearLayout.html#method.save_state
+#[derive(Clone, Debug)]
+struct LayoutState {
+    render_idx: usize,
+    is_kept: bool,
+    is_started: bool,
+    partial_sizes: Vec<Size>,
+}
+
+/// Arranges its elements in columns, continuing from the bottom of one column at the top of the
+/// next column.
+///
+/// The content of this element is a vertical [`LinearLayout`][].  It is rendered into the first
+/// column until it does not fit, then the remaining content is rendered into the next column, and
+/// so on.  If all columns of a page are filled, the content is continued on the next page.  The
+/// columns have the same width and are separated by a gutter that is set with
+/// [`set_gutter`][].  A rule line can be drawn in the gutters with [`set_rule`][].
+///
+/// Per default, the columns are balanced on the last page, i. e. the column height is reduced so
+/// that the content is distributed evenly over the columns.  This requires rendering the content
+/// into measuring areas (see [`render::Area::measuring`][]) several times, so all elements must
+/// support [`Element::reset`][].  The content is measured once when the rendering starts, and
+/// the remaining content is only measured again on the pages that it could fit on.  Balancing can
+/// be deactivated with [`set_balanced`][].
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, style};
+/// let columns = elements::Columns::new(2)
+///     .with_gutter(5)
+///     .with_rule(style::LineStyle::new())
+///     .element(elements::Paragraph::new("first"))
+///     .element(elements::Paragraph::new("second"));
+/// ```
+///
+/// [`LinearLayout`]: struct.LinearLayout.html
+/// [`set_gutter`]: #method.set_gutter
+/// [`set_rule`]: #method.set_rule
+/// [`set_balanced`]: #method.set_balanced
+/// [`render::Area::measuring`]: ../render/struct.Area.html#method.measuring
+/// [`Element::reset`]: ../trait.Element.html#method.reset
+pub struct Columns {
+    layout: LinearLayout,
+    count: usize,
+    gutter: Mm,
+    rule: Option<LineStyle>,
+    balanced: bool,
+    // The estimated height of the content that has not been rendered yet, if it has been measured
+    remaining_height: Option<Mm>,
+}
+
+impl Columns {
+    /// Creates a new empty column layout with the given number of columns.
+    pub fn new(count: usize) -> Columns {
+        Columns {
+            layout: LinearLayout::vertical(),
+            count: count.max(1),
+            gutter: Mm(0.0),
+            rule: None,
+            balanced: true,
+            remaining_height: None,
+        }
+    }
+
+    /// Adds the given element to this layout.
+    pub fn push<E: IntoBoxedElement>(&mut self, element: E) {
+        self.layout.push(element);
+    }
+
+    /// Adds the given element to this layout and it returns the layout.
+    pub fn element<E: IntoBoxedElement>(mut self, element: E) -> Self {
+        self.push(element);
+        self
+    }
+
+    /// Sets the width of the gutter between the columns.
+    pub fn set_gutter(&mut self, gutter: impl Into<Mm>) {
+        self.gutter = gutter.into();
+    }
+
+    /// Sets the width of the gutter between the columns and returns the layout.
+    pub fn with_gutter(mut self, gutter: impl Into<Mm>) -> Self {
+        self.set_gutter(gutter);
+        self
+    }
+
+    /// Sets the line style of the rule line that is drawn in the middle of the gutters.
+    pub fn set_rule(&mut self, line_style: impl Into<LineStyle>) {
+        self.rule = Some(line_style.into());
+    }
+
+    /// Sets the line style of the rule line that is drawn in the middle of the gutters and returns
+    /// the layout.
+    pub fn with_rule(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_rule(line_style);
+        self
+    }
+
+    /// Sets whether the columns are balanced on the last page.
+    pub fn set_balanced(&mut self, balanced: bool) {
+        self.balanced = balanced;
+    }
+
+    /// Sets whether the columns are balanced on the last page and returns the layout.
+    pub fn with_balanced(mut self, balanced: bool) -> Self {
+        self.set_balanced(balanced);
+        self
+    }
+
+    /// Splits the given area into the column areas.
+    fn get_columns<'p>(&self, area: &render::Area<'p>) -> Vec<render::Area<'p>> {
+        let count = self.count as f32;
+        let width = (area.size().width - self.gutter * (count - 1.0)) / count;
+        let mut columns = area.split_horizontally(&vec![1; self.count]);
+        for (i, column) in columns.iter_mut().enumerate() {
+            // Move the columns so that the gutters are between them.
+            column.add_offset(Position::new(self.gutter * i as f32 / count, 0));
+            column.set_width(width.max(Mm(0.0)));
+        }
+        columns
+    }
+
+    /// Renders the remaining content into the given number of measuring columns with the given
+    /// size and returns whether it fits into the columns.  The content has to be restored
+    /// afterwards.
+    fn fits(
+        &mut self,
+        context: &Context,
+        size: Size,
+        columns: usize,
+        style: Style,
+    ) -> Result<bool, Error> {
+        for _ in 0..columns {
+            let result = self
+                .layout
+                .render(context, render::Area::measuring(size), style)?;
+            if !result.has_more {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the size of the measuring columns for the given column size.
+    ///
+    /// The remaining content is measured in columns with the height of an empty page, which is a
+    /// finite bound that also works for the columns on the following pages.
+    fn get_measuring_size(&self, context: &Context, size: Size) -> Size {
+        let height = context.content_height().unwrap_or(size.height);
+        Size::new(size.width, height.max(size.height))
+    }
+
+    /// Renders the remaining content into measuring columns with the given size and returns the
+    /// height of the content.
+    fn get_remaining_height(
+        &mut self,
+        context: &Context,
+        size: Size,
+        style: Style,
+    ) -> Result<Mm, Error> {
+        let area = render::Area::measuring(size);
+        let mut height = Mm(0.0);
+        loop {
+            let result = self.layout.render(context, area.clone(), style)?;
+            height += result.size.height;
+            if !result.has_more || result.size == Size::default() {
+                return Ok(height);
+            }
+        }
+    }
+
+    /// Returns the column height that distributes the remaining content evenly over the columns,
+    /// or `None` if the content does not fit into the columns with the given size.
+    ///
+    /// After each measurement, only the element that has been rendered partially is rendered
+    /// again to restore the state of the content.
+    fn get_balanced_height(
+        &mut self,
+        context: &Context,
+        size: Size,
+        style: Style,
+    ) -> Result<Option<Mm>, Error> {
+        let measuring_size = self.get_measuring_size(context, size);
+        let state = self.layout.save_state();
+        let mut height = None;
+        if self.fits(context, size, self.count, style)? {
+            // Start with the average column height and increase it by the average overflow until
+            // the content fits.
+            let count = self.count as f32;
+            self.layout.restore_state(context, &state, style)?;
+            let mut column_height =
+                self.get_remaining_height(context, measuring_size, style)? / count;
+            while column_height < size.height {
+                let column_size = Size::new(size.width, column_height);
+                self.layout.restore_state(context, &state, style)?;
+                if self.fits(context, column_size, self.count, style)? {
+                    break;
+                }
+                let overflow = self.get_remaining_height(context, measuring_size, style)?;
+                column_height += (overflow / count).max(Mm(0.5));
+            }
+            height = Some(column_height.min(size.height));
+        }
+        self.layout.restore_state(context, &state, style)?;
+        Ok(height)
+    }
+}
+
+impl Element for Columns {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let mut columns = self.get_columns(&area);
+        if self.balanced {
+            let size = columns[0].size();
+            let remaining_height = match self.remaining_height {
+                Some(height) => height,
+                None => {
+                    // Nothing has been rendered yet, so the content can be measured and reset.
+                    let measuring_size = self.get_measuring_size(context, size);
+                    let height = self.get_remaining_height(context, measuring_size, style)?;
+                    self.layout.reset();
+                    height
+                }
+            };
+            // Restoring the state after measuring replays the partially rendered element, so the
+            // content is only measured on the pages that it could fit on, with one column of
+            // tolerance for the different page breaks.
+            if remaining_height <= size.height * (self.count + 1) as f32 {
+                if let Some(height) = self.get_balanced_height(context, size, style)? {
+                    for column in &mut columns {
+                        column.set_height(height);
+                    }
+                }
+            }
+            self.remaining_height = Some(remaining_height);
+        }
+
+        result.has_more = true;
+        let mut used_columns = 0;
+        for column in columns.iter() {
+            let column_result = self.layout.render(context, column.clone(), style)?;
+            used_columns += 1;
+            if let Some(remaining_height) = &mut self.remaining_height {
+                *remaining_height -= column_result.size.height;
+            }
+            result.size.height = result.size.height.max(column_result.size.height);
+            if !column_result.has_more {
+                result.has_more = false;
+                break;
+            }
+        }
+        if result.size.height > Mm(0.0) {
+            result.size.width = area.size().width;
+        }
+
+        if let Some(rule) = self.rule {
+            for column in columns.iter().take(used_columns).skip(1) {
+                let x = self.gutter / -2.0;
+                column.draw_line(
+                    vec![Position::new(x, 0), Position::new(x, result.size.height)],
+                    rule,
+                );
+            }
+        }
+        Ok(result)
+    }
+
+    fn reset(&mut self) {
+        self.layout.reset();
+        self.remaining_height = None;
     }
 }
 
@@ -233,24 +956,28 @@ impl Text {
         Text { text: text.into(), orphan: false, orphan_position: Position::default()}
     }
     /// Sets the orphan
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn set_orphan(&mut self, orphan: bool) {
         self.orphan = orphan;
     }
 
     /// Sets the orphan of this text and returns the text.
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn with_orphan(mut self, orphan: bool) -> Self {
-        self.set_orphan(orphan);
+        self.orphan = orphan;
         self
     }
     
     /// Sets the position
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn set_orphan_position(&mut self, x: impl Into<Mm>, y: impl Into<Mm>) {
         self.orphan_position = Position::new(x,y);
     }
 
     /// Sets the position of this text and returns the text.
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn with_position(mut self, x: impl Into<Mm>, y: impl Into<Mm>) -> Self {
-        self.set_orphan_position(x,y);
+        self.orphan_position = Position::new(x,y);
         self
     }
 }
@@ -303,7 +1030,20 @@ impl Element for Text {
 ///
 /// Use the [`push`][], [`string`][], [`push_styled`][] and [`string_styled`][] methods to add
 /// strings to this paragraph.  Besides the styling of the text (see [`Style`][]), you can also set
-/// an [`Alignment`][] for the paragraph.
+/// an [`Alignment`][] for the paragraph.  Justified paragraphs stretch the space between the words
+/// so that all lines except the last one fill the available width.
+///
+/// Per default, the lines are filled greedily.  The optimal line breaking (see [`LineBreaking`][])
+/// selects the line breaks for the whole paragraph so that the lines are more even.
+///
+/// If the paragraph does not fit on the current page, it is continued on the next page.  The
+/// page break can be controlled with [`set_orphans`][], [`set_widows`][] and
+/// [`set_keep_together`][].
+///
+/// Text that mixes left-to-right and right-to-left scripts is reordered line by line according to
+/// the Unicode Bidirectional Algorithm.  The base direction of the paragraph is detected from the
+/// first strong character unless it is set with [`set_direction`][].  [`Alignment::Start`][] and
+/// [`Alignment::End`][] follow the base direction.
 ///
 /// The line height and spacing are calculated based on the style of each string.
 ///
@@ -331,7 +1071,14 @@ impl Element for Text {
 ///
 /// [`Style`]: ../style/struct.Style.html
 /// [`Alignment`]: ../enum.Alignment.html
+/// [`LineBreaking`]: ../enum.LineBreaking.html
+/// [`Alignment::Start`]: ../enum.Alignment.html#variant.Start
+/// [`Alignment::End`]: ../enum.Alignment.html#variant.End
 /// [`Element::styled`]: ../trait.Element.html#method.styled
+/// [`set_direction`]: #method.set_direction
+/// [`set_orphans`]: #method.set_orphans
+/// [`set_widows`]: #method.set_widows
+/// [`set_keep_together`]: #method.set_keep_together
 /// [`push`]: #method.push
 /// [`push_styled`]: #method.push_styled
 /// [`string`]: #method.string
@@ -340,8 +1087,19 @@ impl Element for Text {
 pub struct Paragraph {
     text: Vec<StyledString>,
     words: collections::VecDeque<StyledString>,
+    // The bidi levels of the bytes of the words
+    levels: Vec<unicode_bidi::Level>,
+    words_loaded: bool,
     style_applied: bool,
     alignment: Alignment,
+    direction: Direction,
+    is_rtl: bool,
+    justify_last_line: bool,
+    line_breaking: LineBreaking,
+    orphans: usize,
+    widows: usize,
+    keep_together: bool,
+    is_started: bool,
 }
 
 impl Paragraph {
@@ -364,6 +1122,101 @@ impl Paragraph {
         self
     }
 
+    /// Sets whether the last line of this paragraph is justified too if the paragraph has the
+    /// [`Alignment::Justified`][] alignment.  Per default, the last line is flushed to the start of
+    /// the line.
+    ///
+    /// [`Alignment::Justified`]: ../enum.Alignment.html#variant.Justified
+    pub fn set_justify_last_line(&mut self, justify_last_line: bool) {
+        self.justify_last_line = justify_last_line;
+    }
+
+    /// Sets whether the last line of this paragraph is justified too and returns the paragraph.
+    pub fn with_justify_last_line(mut self, justify_last_line: bool) -> Self {
+        self.set_justify_last_line(justify_last_line);
+        self
+    }
+
+    /// Sets the line breaking algorithm for this paragraph.
+    pub fn set_line_breaking(&mut self, line_breaking: LineBreaking) {
+        self.line_breaking = line_breaking;
+    }
+
+    /// Sets the line breaking algorithm for this paragraph and returns the paragraph.
+    pub fn with_line_breaking(mut self, line_breaking: LineBreaking) -> Self {
+        self.set_line_breaking(line_breaking);
+        self
+    }
+
+    /// Sets the minimum number of lines of this paragraph that are printed at the bottom of a page
+    /// if the paragraph is continued on the next page (orphan control).
+    ///
+    /// If less lines fit on the page, the paragraph is moved to the next page.  Per default, a
+    /// single line can be left at the bottom of a page.
+    ///
+    /// The orphan and widow control is ignored at the top of a page (see
+    /// [`render::Area::is_at_top`][]) if no line could be printed otherwise, as moving the paragraph
+    /// to the next page would not help.
+    ///
+    /// [`render::Area::is_at_top`]: ../render/struct.Area.html#method.is_at_top
+    pub fn set_orphans(&mut self, orphans: usize) {
+        self.orphans = orphans;
+    }
+
+    /// Sets the minimum number of lines of this paragraph that are printed at the bottom of a page
+    /// and returns the paragraph.
+    pub fn with_orphans(mut self, orphans: usize) -> Self {
+        self.set_orphans(orphans);
+        self
+    }
+
+    /// Sets the minimum number of lines of this paragraph that are printed at the top of a page if
+    /// the paragraph is continued from the previous page (widow control).
+    ///
+    /// If less lines would be left for the next page, the page is broken earlier.  Per default, a
+    /// single line can be left for the top of the next page.  See [`set_orphans`][] for the
+    /// behavior at the top of a page.
+    ///
+    /// [`set_orphans`]: #method.set_orphans
+    pub fn set_widows(&mut self, widows: usize) {
+        self.widows = widows;
+    }
+
+    /// Sets the minimum number of lines of this paragraph that are printed at the top of a page
+    /// and returns the paragraph.
+    pub fn with_widows(mut self, widows: usize) -> Self {
+        self.set_widows(widows);
+        self
+    }
+
+    /// Sets whether this paragraph should be kept together on one page.
+    ///
+    /// If this option is set and the paragraph does not fit on the current page, it is moved to
+    /// the next page if it fits on an empty page (see [`render::Area::empty_height`][]).
+    /// Otherwise, it is broken as usual.
+    ///
+    /// [`render::Area::empty_height`]: ../render/struct.Area.html#method.empty_height
+    pub fn set_keep_together(&mut self, keep_together: bool) {
+        self.keep_together = keep_together;
+    }
+
+    /// Sets whether this paragraph should be kept together on one page and returns the paragraph.
+    pub fn with_keep_together(mut self, keep_together: bool) -> Self {
+        self.set_keep_together(keep_together);
+        self
+    }
+
+    /// Sets the base direction of the text in this paragraph.
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    /// Sets the base direction of the text in this paragraph and returns the paragraph.
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.set_direction(direction);
+        self
+    }
+
     /// Adds a string to the end of this paragraph.
     pub fn push(&mut self, s: impl Into<StyledString>) {
         self.text.push(s.into());
@@ -387,10 +1240,94 @@ impl Paragraph {
     }
 
     fn get_offset(&self, width: Mm, max_width: Mm) -> Mm {
-        match self.alignment {
-            Alignment::Left => Mm::default(),
-            Alignment::Center => (max_width - width) / 2.0,
-            Alignment::Right => max_width - width,
+        match (self.alignment, self.is_rtl) {
+            (Alignment::Left, _)
+            | (Alignment::Start, false)
+            | (Alignment::End, true)
+            | (Alignment::Justified, false) => Mm::default(),
+            (Alignment::Center, _) => (max_width - width) / 2.0,
+            (Alignment::Right, _)
+            | (Alignment::Start, true)
+            | (Alignment::End, false)
+            | (Alignment::Justified, true) => max_width - width,
+        }
+    }
+
+    /// Returns the number of lines with the given metrics that should be printed into an area with
+    /// the given area, considering the widow and orphan control and the keep-together option.
+    fn get_line_count(&self, metrics: &[fonts::Metrics], area: &render::Area<'_>) -> usize {
+        let mut remaining = area.size().height;
+        let mut count = 0;
+        for m in metrics {
+            if m.glyph_height > remaining {
+                break;
+            }
+            remaining -= m.line_height;
+            count += 1;
+        }
+        if count == metrics.len() {
+            return count;
+        }
+
+        if self.keep_together && !self.is_started && !area.is_at_top() {
+            let total_height: Mm = metrics.iter().map(|m| m.line_height).sum();
+            if total_height <= area.empty_height() {
+                return 0;
+            }
+        }
+        let limited_count = count.min(metrics.len().saturating_sub(self.widows));
+        if limited_count > 0 && limited_count >= self.orphans {
+            limited_count
+        } else if area.is_at_top() {
+            // The paragraph would not fit on the next page either.
+            count
+        } else {
+            0
+        }
+    }
+
+    /// Returns the additional space after every space character that is needed to justify the
+    /// given line, or zero if the line should not be justified.
+    ///
+    /// Trailing spaces are removed from the line if it is justified.
+    fn get_word_spacing(
+        &self,
+        context: &Context,
+        line: &mut Vec<StyledCow<'_>>,
+        is_last: bool,
+        max_width: Mm,
+    ) -> Mm {
+        if self.alignment != Alignment::Justified || (is_last && !self.justify_last_line) {
+            return Mm::default();
+        }
+        while let Some(last) = line.last_mut() {
+            let len = last.s.trim_end_matches(' ').len();
+            if len == 0 {
+                line.pop();
+            } else {
+                last.s.to_mut().truncate(len);
+                break;
+            }
+        }
+        let spaces = line.iter().map(|s| s.s.matches(' ').count()).sum::<usize>();
+        if spaces == 0 {
+            return Mm::default();
+        }
+        let width: Mm = line.iter().map(|s| s.width(&context.font_cache)).sum();
+        (max_width - width).max(Mm::default()) / spaces as f32
+    }
+
+    fn load_words(&mut self) {
+        if !self.words_loaded {
+            self.words = wrap::Words::new(self.text.clone()).collect();
+            self.is_rtl = match self.direction {
+                Direction::Auto => wrap::is_rtl(self.text.iter().map(|s| s.s.as_str())),
+                Direction::LeftToRight => false,
+                Direction::RightToLeft => true,
+            };
+            let text: String = self.text.iter().map(|s| s.s.as_str()).collect();
+            self.levels = wrap::resolve_levels(&text, self.is_rtl);
+            self.words_loaded = true;
         }
     }
 
@@ -403,33 +1340,31 @@ impl Paragraph {
         }
     }
     
-    /// Knowing the height of a paragraph: is necessary to adjust margins and be able to use a footer.
-    pub fn get_height(
-        &mut self,
-        context: &Context,
-        // mut area: render::Area<'_>,
-        width: impl Into<Mm>,
-        // _style: Style,
-       ) -> Mm {
-           let mut result = Mm(0.0);
-            if self.words.is_empty() {
-                if self.text.is_empty() {
-                    return result;
-                }
-                self.words = wrap::Words::new(mem::take(&mut self.text)).collect();
-            }
-            let words = self.words.iter().map(Into::into);            
-            let mut wrapper = wrap::Wrapper::new(words, context, width.into());
-            
-            for (line, _delta) in &mut wrapper {
-                let metrics = line
-                    .iter()
-                    .map(|s| s.style.metrics(&context.font_cache))
-                    .fold(fonts::Metrics::default(), |max, m| max.max(&m));
-                result+=metrics.line_height;
-            }
-            result
-       }
+    /// Returns the height of this paragraph if it is wrapped with the given width.
+    ///
+    /// Unlike [`Element::measure`][], this method does not apply a style to the paragraph and does
+    /// not limit the height.
+    ///
+    /// [`Element::measure`]: ../trait.Element.html#method.measure
+    #[deprecated(note = "use Element::measure instead")]
+    pub fn get_height(&mut self, context: &Context, width: impl Into<Mm>) -> Mm {
+        let mut result = Mm(0.0);
+        self.load_words();
+        if self.words.is_empty() {
+            return result;
+        }
+        let words = self.words.iter().map(Into::into);
+        let wrapper =
+            wrap::Wrapper::new(words, context, width.into()).with_line_breaking(self.line_breaking);
+        for (line, _delta) in wrapper {
+            let metrics = line
+                .iter()
+                .map(|s| s.style.metrics(&context.font_cache))
+                .fold(fonts::Metrics::default(), |max, m| max.max(&m));
+            result += metrics.line_height;
+        }
+        result
+    }
 }
 
 impl Element for Paragraph {
@@ -443,31 +1378,80 @@ impl Element for Paragraph {
 
         self.apply_style(style);
 
+        self.load_words();
         if self.words.is_empty() {
-            if self.text.is_empty() {
-                return Ok(result);
-            }
-            self.words = wrap::Words::new(mem::take(&mut self.text)).collect();
+            return Ok(result);
         }
 
         let words = self.words.iter().map(Into::into);
         let mut rendered_len = 0;
-        let mut wrapper = wrap::Wrapper::new(words, context, area.size().width);
-        for (line, delta) in &mut wrapper {
-            let width = line.iter().map(|s| s.width(&context.font_cache)).sum();
+        // Lines that overlap a float have different widths, so we have to fill them greedily.
+        let line_breaking = if area.has_floats() {
+            LineBreaking::Greedy
+        } else {
+            self.line_breaking
+        };
+        let mut wrapper =
+            wrap::Wrapper::new(words, context, area.size().width).with_line_breaking(line_breaking);
+        // Wrap the remaining text completely so that we know where to break the page
+        let line_height = style.metrics(&context.font_cache).line_height;
+        let mut lines = Vec::new();
+        let mut skipped_lines = Vec::new();
+        let mut y = Mm(0.0);
+        loop {
+            let (offset, max_width) = area.line_bounds(y, line_height);
+            if max_width <= Mm(0.0) && area.size().width > Mm(0.0) {
+                // Leave the lines that are completely covered by a float empty.
+                let metrics = style.metrics(&context.font_cache);
+                skipped_lines.push((Vec::new(), 0, metrics, offset, max_width));
+                y += metrics.line_height;
+                continue;
+            }
+            wrapper.set_width(max_width);
+            let (line, delta) = match wrapper.next() {
+                Some(line) => line,
+                None => break,
+            };
             // Calculate the maximum line height
             let metrics = line
                 .iter()
                 .map(|s| s.style.metrics(&context.font_cache))
                 .fold(fonts::Metrics::default(), |max, m| max.max(&m));
-            let position = Position::new(self.get_offset(width, area.size().width), 0);
+            y += metrics.line_height;
+            lines.append(&mut skipped_lines);
+            lines.push((line, delta, metrics, offset, max_width));
+        }
+        let metrics: Vec<_> = lines.iter().map(|(_, _, metrics, _, _)| *metrics).collect();
+        let line_count = self.get_line_count(&metrics, &area);
+        self.is_started = true;
+        let total_count = lines.len();
+        result.has_more = line_count < total_count;
+
+        let lines = lines.into_iter().take(line_count).enumerate();
+        for (idx, (mut line, delta, metrics, offset, max_width)) in lines {
+            let line_len = line.iter().map(|s| s.s.len()).sum::<usize>();
+            let word_spacing =
+                self.get_word_spacing(context, &mut line, idx + 1 == total_count, max_width);
+            let mut width = line.iter().map(|s| s.width(&context.font_cache)).sum();
+            if word_spacing > Mm::default() {
+                width = max_width;
+            }
+            let position = Position::new(offset + self.get_offset(width, max_width), 0);
 
             if let Some(mut section) = area.text_section(&context.font_cache, position, metrics) {
-                for s in line {
-                    section.print_str(&s.s, s.style)?;
-                    rendered_len += s.s.len();
+                section.set_word_spacing(word_spacing);
+                let line_levels = self
+                    .levels
+                    .get(rendered_len..rendered_len + line_len - delta)
+                    .unwrap_or_default();
+                for (s, is_rtl) in wrap::reorder(&line, line_levels, self.is_rtl) {
+                    if is_rtl {
+                        section.print_rtl_str(s.s, s.style)?;
+                    } else {
+                        section.print_str(s.s, s.style)?;
+                    }
                 }
-                rendered_len -= delta;
+                rendered_len += line_len - delta;
             } else {
                 result.has_more = true;
                 break;
@@ -490,6 +1474,7 @@ impl Element for Paragraph {
 
         // Remove the rendered data from self.words so that we don’t render it again on the next
         // call to render.
+        self.levels.drain(..rendered_len.min(self.levels.len()));
         while rendered_len > 0 && !self.words.is_empty() {
             if self.words[0].s.len() <= rendered_len {
                 rendered_len -= self.words[0].s.len();
@@ -502,6 +1487,33 @@ impl Element for Paragraph {
 
         Ok(result)
     }
+
+    fn reset(&mut self) {
+        self.words.clear();
+        self.levels.clear();
+        self.words_loaded = false;
+        self.is_started = false;
+    }
+
+    /// Measures this paragraph without changing its rendering state, so unlike the default
+    /// implementation, this method can also be called for a paragraph that has been rendered
+    /// partially.
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.load_words();
+        let words = self.words.clone();
+        let levels = self.levels.clone();
+        let is_started = self.is_started;
+        let result = self.render(context, area.to_measuring(), style);
+        self.words = words;
+        self.levels = levels;
+        self.is_started = is_started;
+        result
+    }
 }
 
 impl From<Vec<StyledString>> for Paragraph {
@@ -529,6 +1541,285 @@ impl<T: Into<StyledString>> iter::FromIterator<T> for Paragraph {
     }
 }
 
+/// A heading that is added to the outline (the bookmarks) of the document.
+///
+/// The heading is rendered like a [`Paragraph`][] with the given text.  When it is rendered, a
+/// bookmark with the heading text and the level of the heading is added at its position, see
+/// [`render::Area::add_bookmark`][].  The level starts at 1 for top-level headings, and headings
+/// with a higher level are nested under the preceding heading with a lower level.
+///
+/// The heading is also registered in the [`Context`][] with a named destination at its position
+/// so that it can be listed in a [`TableOfContents`][].
+///
+/// The heading does not set a style, so you typically want to combine it with a style, for
+/// example using [`Element::styled`][].
+///
+/// # Examples
+///
+/// ```
+/// use rckive_genpdf::{elements, style, Element as _};
+/// let chapter = elements::Heading::new("Introduction").styled(style::Style::new().bold());
+/// let section = elements::Heading::new("Motivation").with_level(2);
+/// ```
+///
+/// [`Paragraph`]: struct.Paragraph.html
+/// [`render::Area::add_bookmark`]: ../render/struct.Area.html#method.add_bookmark
+/// [`Context`]: ../struct.Context.html
+/// [`TableOfContents`]: struct.TableOfContents.html
+/// [`Element::styled`]: ../trait.Element.html#method.styled
+#[derive(Clone, Debug)]
+pub struct Heading {
+    paragraph: Paragraph,
+    title: String,
+    level: usize,
+    is_first: bool,
+}
+
+impl Heading {
+    /// Creates a new top-level heading with the given text.
+    pub fn new(text: impl Into<StyledString>) -> Heading {
+        let text = text.into();
+        Heading {
+            title: text.s.clone(),
+            paragraph: Paragraph::new(text),
+            level: 1,
+            is_first: true,
+        }
+    }
+
+    /// Sets the level of this heading, starting at 1 for top-level headings.
+    pub fn set_level(&mut self, level: usize) {
+        self.level = level;
+    }
+
+    /// Sets the level of this heading and returns the heading.
+    pub fn with_level(mut self, level: usize) -> Self {
+        self.set_level(level);
+        self
+    }
+
+    /// Sets the alignment of this heading.
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.paragraph.set_alignment(alignment);
+    }
+
+    /// Sets the alignment of this heading and returns the heading.
+    pub fn aligned(mut self, alignment: Alignment) -> Self {
+        self.set_alignment(alignment);
+        self
+    }
+
+    /// Returns the title of this heading as used in the outline.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the level of this heading.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+}
+
+impl Element for Heading {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = self.paragraph.render(context, area.clone(), style)?;
+        // Wait for the first page that the heading is actually rendered on.
+        if self.is_first && (result.size.height > Mm(0.0) || !result.has_more) {
+            if !area.is_measuring() {
+                let destination = context.register_heading(&self.title, self.level);
+                area.add_destination(destination);
+                area.add_bookmark(&self.title, self.level);
+            }
+            self.is_first = false;
+        }
+        Ok(result)
+    }
+
+    fn reset(&mut self) {
+        self.paragraph.reset();
+        self.is_first = true;
+    }
+}
+
+/// A table of contents that lists the headings of the document.
+///
+/// Every [`Heading`][] of the document is printed with its title, dot leaders and its page number.
+/// Long titles are wrapped within the width that is left next to the page number.  Nested headings are indented according to their level, and each entry
+/// links to its heading unless links are disabled with [`set_links`][].
+///
+/// The headings are taken from the previous rendering process, see [`Context::headings`][].  The
+/// document is rendered again until the page numbers are stable, so the table of contents can be
+/// placed before the headings that it lists.
+///
+/// # Examples
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut doc = rckive_genpdf::Document::new(
+///     rckive_genpdf::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+///         .expect("Failed to load font family"),
+/// );
+/// doc.push(elements::TableOfContents::new().with_max_level(2));
+/// doc.push(elements::PageBreak::new());
+/// doc.push(elements::Heading::new("Introduction"));
+/// doc.push(elements::Heading::new("Motivation").with_level(2));
+/// ```
+///
+/// [`Heading`]: struct.Heading.html
+/// [`Context::headings`]: ../struct.Context.html#method.headings
+/// [`set_links`]: #method.set_links
+#[derive(Clone, Debug)]
+pub struct TableOfContents {
+    indent: Mm,
+    max_level: Option<usize>,
+    has_links: bool,
+    entries: Option<collections::VecDeque<HeadingInfo>>,
+}
+
+impl TableOfContents {
+    /// Creates a new table of contents that lists all headings with links to the headings.
+    pub fn new() -> TableOfContents {
+        TableOfContents {
+            indent: Mm::from(5),
+            max_level: None,
+            has_links: true,
+            entries: None,
+        }
+    }
+
+    /// Sets the indentation per heading level (defaults to 5 mm).
+    pub fn set_indent(&mut self, indent: impl Into<Mm>) {
+        self.indent = indent.into();
+    }
+
+    /// Sets the indentation per heading level and returns the table of contents.
+    pub fn with_indent(mut self, indent: impl Into<Mm>) -> Self {
+        self.set_indent(indent);
+        self
+    }
+
+    /// Sets the maximum level of the headings that are listed.
+    pub fn set_max_level(&mut self, max_level: usize) {
+        self.max_level = Some(max_level);
+    }
+
+    /// Sets the maximum level of the headings that are listed and returns the table of contents.
+    pub fn with_max_level(mut self, max_level: usize) -> Self {
+        self.set_max_level(max_level);
+        self
+    }
+
+    /// Sets whether the entries link to their headings (defaults to `true`).
+    pub fn set_links(&mut self, has_links: bool) {
+        self.has_links = has_links;
+    }
+
+    /// Sets whether the entries link to their headings and returns the table of contents.
+    pub fn with_links(mut self, has_links: bool) -> Self {
+        self.set_links(has_links);
+        self
+    }
+
+    /// Wraps the given title of an entry within the given width.
+    fn wrap_title(context: &Context, title: &str, style: Style, width: Mm) -> Vec<String> {
+        let words: Vec<_> = wrap::Words::new(vec![StyledString::new(title, style)]).collect();
+        let lines: Vec<String> = wrap::Wrapper::new(words.iter().map(Into::into), context, width)
+            .map(|(line, _)| {
+                let line: String = line.iter().map(|s| s.s.as_ref()).collect();
+                line.trim_end().to_owned()
+            })
+            .collect();
+        if lines.is_empty() {
+            vec![title.to_owned()]
+        } else {
+            lines
+        }
+    }
+}
+
+impl Default for TableOfContents {
+    fn default() -> TableOfContents {
+        TableOfContents::new()
+    }
+}
+
+impl Element for TableOfContents {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let max_level = self.max_level;
+        let entries = self.entries.get_or_insert_with(|| {
+            context
+                .headings()
+                .iter()
+                .filter(|heading| max_level.map(|max| heading.level <= max).unwrap_or(true))
+                .cloned()
+                .collect()
+        });
+
+        let font_cache = &context.font_cache;
+        let line_height = style.line_height(font_cache);
+        let space_width = style.char_width(font_cache, ' ');
+        let leader_width = style.char_width(font_cache, '.');
+        let width = area.size().width;
+        while let Some(entry) = entries.front() {
+            let indent = self.indent * entry.level.saturating_sub(1) as f32;
+            let page = entry.page.to_string();
+            let page_width = style.str_width(font_cache, &page);
+            let title_width = width - indent - page_width - space_width * 2.0;
+            let lines = Self::wrap_title(context, &entry.title, style, title_width.max(Mm(0.0)));
+            let height = line_height * lines.len() as f32;
+            if height > area.size().height {
+                result.has_more = true;
+                break;
+            }
+
+            let mut y = Mm(0.0);
+            for line in &lines {
+                area.print_str(font_cache, Position::new(indent, y), style, line)?;
+                y += line_height;
+            }
+            // The leaders and the page number are printed on the last line of the title.
+            let y = y - line_height;
+            let last_line = lines.last().map(String::as_str).unwrap_or_default();
+            let leader_space = title_width - style.str_width(font_cache, last_line);
+            if leader_space > leader_width {
+                let leaders = ".".repeat((leader_space.0 / leader_width.0) as usize);
+                let leaders_width = style.str_width(font_cache, &leaders);
+                let x = width - page_width - space_width - leaders_width;
+                area.print_str(font_cache, Position::new(x, y), style, leaders)?;
+            }
+            let x = width - page_width;
+            area.print_str(font_cache, Position::new(x, y), style, page)?;
+
+            if self.has_links {
+                area.add_link(
+                    Position::new(indent, 0),
+                    Size::new(width - indent, height),
+                    &Link::destination(entry.destination.as_str()),
+                );
+            }
+            area.add_offset(Position::new(0, height));
+            result.size = result.size.stack_vertical(Size::new(width, height));
+            entries.pop_front();
+        }
+        Ok(result)
+    }
+
+    fn reset(&mut self) {
+        self.entries = None;
+    }
+}
+
 /// A line break.
 ///
 /// This element inserts a given number of empty lines.
@@ -542,13 +1833,16 @@ impl<T: Into<StyledString>> iter::FromIterator<T> for Paragraph {
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Break {
     lines: f32,
+    remaining: f32,
 }
 
 impl Break {
     /// Creates a new break with the given number of lines.
     pub fn new(lines: impl Into<f32>) -> Break {
+        let lines = lines.into();
         Break {
-            lines: lines.into(),
+            lines,
+            remaining: lines,
         }
     }
 }
@@ -562,34 +1856,47 @@ impl Element for Break {
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
         //allow line break in negative
-        if self.lines == 0.0 {
+        if self.remaining == 0.0 {
             return Ok(result);
         }
         let line_height = style.line_height(&context.font_cache);
-        let break_height = line_height * self.lines;
+        let break_height = line_height * self.remaining;
         if break_height < area.size().height {
             result.size.height = break_height;
-            self.lines = 0.0;
+            self.remaining = 0.0;
         } else {
             result.size.height = area.size().height;
-            self.lines -= result.size.height.0 / line_height.0;
+            self.remaining -= result.size.height.0 / line_height.0;
         }
         Ok(result)
     }
+
+    fn reset(&mut self) {
+        self.remaining = self.lines;
+    }
 }
 
 /// A page break.
 ///
-/// This element inserts a page break.
+/// This element inserts a page break.  If a size is set with [`set_size`][] or [`with_size`][],
+/// the following pages have this paper size, for example to insert landscape pages for wide
+/// tables.  Measuring the page break (see [`Element::measure`][]) does not change the paper size.
 ///
 /// # Example
 ///
 /// ```
-/// let pb = rckive_genpdf::elements::PageBreak::new();
+/// use rckive_genpdf::{elements, PaperSize, Size};
+/// let pb = elements::PageBreak::new();
+/// let landscape = elements::PageBreak::new().with_size(Size::from(PaperSize::A4).landscape());
 /// ```
+///
+/// [`set_size`]: #method.set_size
+/// [`with_size`]: #method.with_size
+/// [`Element::measure`]: ../trait.Element.html#method.measure
 #[derive(Clone, Copy, Debug, Default)]
 pub struct PageBreak {
     cont: bool,
+    size: Option<Size>,
 }
 
 impl PageBreak {
@@ -597,18 +1904,35 @@ impl PageBreak {
     pub fn new() -> PageBreak {
         PageBreak::default()
     }
+
+    /// Sets the paper size of the pages after this page break.
+    pub fn set_size(&mut self, size: impl Into<Size>) {
+        self.size = Some(size.into());
+    }
+
+    /// Sets the paper size of the pages after this page break and returns the page break.
+    pub fn with_size(mut self, size: impl Into<Size>) -> Self {
+        self.set_size(size);
+        self
+    }
 }
 
 impl Element for PageBreak {
     fn render(
         &mut self,
-        _context: &Context,
-        _area: render::Area<'_>,
+        context: &Context,
+        area: render::Area<'_>,
         _style: Style,
     ) -> Result<RenderResult, Error> {
         if self.cont {
             Ok(RenderResult::default())
         } else {
+            if let Some(size) = self.size {
+                // Measuring the page break must not change the size of the next page.
+                if area.is_layout() {
+                    context.set_next_page_size(size);
+                }
+            }
             // We don’t use (0,0) as the size as this might abort the render process if this is the
             // first element on a new page, see the Rendering Process section of the crate
             // documentation.
@@ -619,6 +1943,10 @@ impl Element for PageBreak {
             })
         }
     }
+
+    fn reset(&mut self) {
+        self.cont = false;
+    }
 }
 
 /// Adds a padding to the wrapped element.
@@ -674,6 +2002,10 @@ impl<E: Element> Element for PaddedElement<E> {
         result.size.height += self.padding.top + self.padding.bottom;
         Ok(result)
     }
+
+    fn reset(&mut self) {
+        self.element.reset();
+    }
 }
 
 /// Adds a default style to the wrapped element and its children.
@@ -723,35 +2055,577 @@ impl<E: Element> Element for StyledElement<E> {
         style.merge(self.style);
         self.element.render(context, area, style)
     }
+
+    fn reset(&mut self) {
+        self.element.reset();
+    }
 }
 
-/// Adds a frame around the wrapped element.
+/// Turns the wrapped element into a clickable link.
+///
+/// The link annotation covers the area that the wrapped element has been rendered to on every
+/// page.  To add links to parts of a text, register the link with [`Document::add_link`][] and set
+/// it in the [`Style`][] of the text instead, see [`Link`][].
 ///
 /// # Examples
 ///
 /// Direct usage:
 /// ```
-/// use rckive_genpdf::elements;
-/// let p = elements::FramedElement::new(
+/// use rckive_genpdf::{elements, style};
+/// let p = elements::LinkedElement::new(
 ///     elements::Paragraph::new("text"),
+///     style::Link::uri("https://example.com"),
 /// );
 /// ```
 ///
-/// Using [`Element::framed`][]:
+/// Using [`Element::linked`][]:
 /// ```
 /// use rckive_genpdf::{elements, style, Element as _};
-/// let p = elements::Paragraph::new("text").framed(style::LineStyle::new());
+/// let p = elements::Paragraph::new("text")
+///     .linked(style::Link::uri("https://example.com"));
 /// ```
 ///
-/// [`Element::framed`]: ../trait.Element.html#method.framed
-#[derive(Clone, Debug, Default)]
-pub struct FramedElement<E: Element> {
+/// [`Style`]: ../style/struct.Style.html
+/// [`Link`]: ../style/enum.Link.html
+/// [`Document::add_link`]: ../struct.Document.html#method.add_link
+/// [`Element::linked`]: ../trait.Element.html#method.linked
+#[derive(Clone, Debug)]
+pub struct LinkedElement<E: Element> {
     element: E,
-    is_first: bool,
-    line_style: LineStyle,
-    left: bool,
-    right: bool,
-    top: bool,
+    link: Link,
+}
+
+impl<E: Element> LinkedElement<E> {
+    /// Creates a new linked element that wraps the given element with the given link target.
+    pub fn new(element: E, link: Link) -> LinkedElement<E> {
+        LinkedElement { element, link }
+    }
+}
+
+impl<E: Element> Element for LinkedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = self.element.render(context, area.clone(), style)?;
+        if result.size != Size::new(0, 0) {
+            area.add_link(Position::default(), result.size, &self.link);
+        }
+        Ok(result)
+    }
+
+    fn reset(&mut self) {
+        self.element.reset();
+    }
+}
+
+/// Adds a named destination at the beginning of the wrapped element.
+///
+/// The destination can be used as the target of a [`Link::Destination`][].  It is placed at the
+/// upper edge of the first page that the wrapped element is rendered on.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use rckive_genpdf::elements;
+/// let p = elements::AnchoredElement::new(
+///     elements::Paragraph::new("Appendix"),
+///     "appendix",
+/// );
+/// ```
+///
+/// Using [`Element::anchored`][]:
+/// ```
+/// use rckive_genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("Appendix").anchored("appendix");
+/// ```
+///
+/// [`Link::Destination`]: ../style/enum.Link.html#variant.Destination
+/// [`Element::anchored`]: ../trait.Element.html#method.anchored
+#[derive(Clone, Debug)]
+pub struct AnchoredElement<E: Element> {
+    element: E,
+    name: String,
+    is_first: bool,
+}
+
+impl<E: Element> AnchoredElement<E> {
+    /// Creates a new anchored element that wraps the given element and adds a named destination
+    /// with the given name.
+    pub fn new(element: E, name: impl Into<String>) -> AnchoredElement<E> {
+        AnchoredElement {
+            element,
+            name: name.into(),
+            is_first: true,
+        }
+    }
+}
+
+impl<E: Element> Element for AnchoredElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = self.element.render(context, area.clone(), style)?;
+        // Wait for the first page that the element is actually rendered on.
+        if self.is_first && (result.size.height > Mm(0.0) || !result.has_more) {
+            area.add_destination(&self.name);
+            self.is_first = false;
+        }
+        Ok(result)
+    }
+
+    fn reset(&mut self) {
+        self.element.reset();
+        self.is_first = true;
+    }
+}
+
+/// Keeps the wrapped element together on one page.
+///
+/// Before the wrapped element is rendered, it is rendered into a measuring area (see
+/// [`render::Area::to_measuring`][]) and reset.  If it would not fit into the remaining area of
+/// the current page, nothing is rendered and the element is moved to the next page.  If the element
+/// does not fit on an empty page either (see [`render::Area::empty_height`][]), it is broken as
+/// usual.  The wrapped element has to support [`Element::reset`][].
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use rckive_genpdf::elements;
+/// let p = elements::KeepTogether::new(
+///     elements::Paragraph::new("text"),
+/// );
+/// ```
+///
+/// Using [`Element::kept_together`][]:
+/// ```
+/// use rckive_genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("text").kept_together();
+/// ```
+///
+/// [`render::Area::to_measuring`]: ../render/struct.Area.html#method.to_measuring
+/// [`render::Area::empty_height`]: ../render/struct.Area.html#method.empty_height
+/// [`Element::reset`]: ../trait.Element.html#method.reset
+/// [`Element::kept_together`]: ../trait.Element.html#method.kept_together
+#[derive(Clone, Debug)]
+pub struct KeepTogether<E: Element> {
+    element: E,
+    is_first: bool,
+}
+
+impl<E: Element> KeepTogether<E> {
+    /// Creates a new wrapper that keeps the given element together on one page.
+    pub fn new(element: E) -> KeepTogether<E> {
+        KeepTogether {
+            element,
+            is_first: true,
+        }
+    }
+
+    /// Returns whether the wrapped element fits completely into the given area.
+    fn fits(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<bool, Error> {
+        let result = self.element.measure(context, area, style)?;
+        Ok(!result.has_more)
+    }
+}
+
+impl<E: Element> Element for KeepTogether<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.is_first {
+            self.is_first = false;
+            if !area.is_at_top() && !self.fits(context, area.clone(), style)? {
+                let size = Size::new(area.size().width, area.empty_height());
+                if self.fits(context, render::Area::measuring(size), style)? {
+                    return Ok(RenderResult {
+                        has_more: true,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        self.element.render(context, area, style)
+    }
+
+    fn reset(&mut self) {
+        self.element.reset();
+        self.is_first = true;
+    }
+}
+
+/// The side of the area that a [`Float`][] is placed at.
+///
+/// [`Float`]: struct.Float.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FloatSide {
+    /// The float is placed at the left edge of the area and the text flows on its right side.
+    Left,
+    /// The float is placed at the right edge of the area and the text flows on its left side.
+    Right,
+}
+
+/// Places the wrapped element at the left or right edge of the area so that the following text
+/// flows around it.
+///
+/// The element is rendered at the current position, and the rectangle that it covers (plus the
+/// gap set with [`set_gap`][]) is reserved in the area using [`render::Area::add_float`][].  The
+/// float itself does not use vertical space, so the following [`Paragraph`][]s start at the same
+/// position and their lines next to the float use the remaining width.  Other elements do not
+/// flow around the float.
+///
+/// If the element does not fit into the remaining space of the page, it is moved to the next page
+/// like with [`KeepTogether`][].  The width of the element can be limited with [`set_width`][].
+/// For floats at the right edge, the element is measured first, so it has to support
+/// [`Element::reset`][].
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let layout = elements::LinearLayout::vertical()
+///     .element(elements::Float::right(elements::Paragraph::new("Note")).with_width(40))
+///     .element(elements::Paragraph::new("text"));
+/// ```
+///
+/// [`set_gap`]: #method.set_gap
+/// [`set_width`]: #method.set_width
+/// [`render::Area::add_float`]: ../render/struct.Area.html#method.add_float
+/// [`Paragraph`]: struct.Paragraph.html
+/// [`KeepTogether`]: struct.KeepTogether.html
+/// [`Element::reset`]: ../trait.Element.html#method.reset
+#[derive(Clone, Debug)]
+pub struct Float<E: Element> {
+    element: KeepTogether<E>,
+    side: FloatSide,
+    width: Option<Mm>,
+    gap: Mm,
+    is_split: bool,
+}
+
+impl<E: Element> Float<E> {
+    /// Creates a new float with the given element at the given side of the area.
+    pub fn new(element: E, side: FloatSide) -> Float<E> {
+        Float {
+            element: KeepTogether::new(element),
+            side,
+            width: None,
+            gap: Mm(0.0),
+            is_split: false,
+        }
+    }
+
+    /// Creates a new float with the given element at the left edge of the area.
+    pub fn left(element: E) -> Float<E> {
+        Float::new(element, FloatSide::Left)
+    }
+
+    /// Creates a new float with the given element at the right edge of the area.
+    pub fn right(element: E) -> Float<E> {
+        Float::new(element, FloatSide::Right)
+    }
+
+    /// Sets the maximum width of the wrapped element.
+    ///
+    /// If this method is not called, the element can use the full width of the area.
+    pub fn set_width(&mut self, width: impl Into<Mm>) {
+        self.width = Some(width.into());
+    }
+
+    /// Sets the maximum width of the wrapped element and returns the float.
+    pub fn with_width(mut self, width: impl Into<Mm>) -> Self {
+        self.set_width(width);
+        self
+    }
+
+    /// Sets the gap between the element and the text that flows around it.
+    pub fn set_gap(&mut self, gap: impl Into<Mm>) {
+        self.gap = gap.into();
+    }
+
+    /// Sets the gap between the element and the text that flows around it and returns the float.
+    pub fn with_gap(mut self, gap: impl Into<Mm>) -> Self {
+        self.set_gap(gap);
+        self
+    }
+}
+
+impl<E: Element> Element for Float<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.is_split {
+            // The element did not fit on a page, so the rest is rendered like a regular element.
+            return self.element.render(context, area, style);
+        }
+
+        let mut element_area = area.clone();
+        if let Some(width) = self.width {
+            element_area.set_width(width.min(area.size().width));
+        }
+        if self.side == FloatSide::Right {
+            let width = self
+                .element
+                .measure(context, element_area.clone(), style)?
+                .size
+                .width;
+            element_area = area.clone();
+            element_area.add_offset(Position::new(area.size().width - width, 0));
+        }
+
+        let result = self.element.render(context, element_area, style)?;
+        if result.has_more {
+            self.is_split = result.size.height > Mm(0.0);
+            return Ok(result);
+        }
+
+        let size = Size::new(result.size.width + self.gap, result.size.height + self.gap);
+        let x = match self.side {
+            FloatSide::Left => Mm(0.0),
+            FloatSide::Right => area.size().width - size.width,
+        };
+        area.add_float(Position::new(x, 0), size);
+        Ok(RenderResult {
+            size: Size::new(result.size.width, 0),
+            has_more: false,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.element.reset();
+        self.is_split = false;
+    }
+}
+
+/// A text or an image that is drawn across a page, for example a “DRAFT” stamp.
+///
+/// The watermark is centered in the area it is rendered into and rotated, per default along the
+/// diagonal from the lower left to the upper right corner.  It is scaled so that its rotated
+/// bounding box fills the given fraction of the area (see [`set_fill`][]) and drawn with the
+/// given opacity (see [`set_opacity`][]).  The watermark does not take any space in the layout.
+///
+/// Use [`Document::push_watermark`][] to draw the watermark on all or some pages of a document.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, style};
+/// let watermark = elements::Watermark::text("CONFIDENTIAL")
+///     .with_color(style::Color::Rgb(200, 0, 0))
+///     .with_opacity(0.25)
+///     .with_rotation(-30.0);
+/// ```
+///
+/// [`set_fill`]: #method.set_fill
+/// [`set_opacity`]: #method.set_opacity
+/// [`Document::push_watermark`]: ../struct.Document.html#method.push_watermark
+pub struct Watermark {
+    element: Box<dyn Element>,
+    rotation: Option<Rotation>,
+    opacity: f32,
+    fill: f32,
+    color: Option<Color>,
+    z_order: i32,
+}
+
+impl Watermark {
+    /// Creates a new watermark with the given element.
+    ///
+    /// The element is measured with the size of the area the watermark is rendered into, and it
+    /// should fit on a single page.
+    pub fn new<E: IntoBoxedElement>(element: E) -> Watermark {
+        Watermark {
+            element: element.into_boxed_element(),
+            rotation: None,
+            opacity: 0.3,
+            fill: 0.8,
+            color: None,
+            z_order: -1,
+        }
+    }
+
+    /// Creates a new watermark with the given text.
+    pub fn text(text: impl Into<StyledString>) -> Watermark {
+        Watermark::new(Text::new(text))
+    }
+
+    /// Creates a new watermark with the given image.
+    #[cfg(feature = "images")]
+    pub fn image(image: Image) -> Watermark {
+        Watermark::new(image)
+    }
+
+    /// Sets the clockwise rotation of this watermark.
+    ///
+    /// Per default, the watermark is rotated along the diagonal from the lower left to the upper
+    /// right corner of the area.
+    pub fn set_rotation(&mut self, rotation: impl Into<Rotation>) {
+        self.rotation = Some(rotation.into());
+    }
+
+    /// Sets the clockwise rotation of this watermark and returns the watermark.
+    pub fn with_rotation(mut self, rotation: impl Into<Rotation>) -> Self {
+        self.set_rotation(rotation);
+        self
+    }
+
+    /// Sets the opacity of this watermark from 0.0 (invisible) to 1.0 (opaque).
+    ///
+    /// Per default, the opacity is 0.3.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    /// Sets the opacity of this watermark and returns the watermark.
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.set_opacity(opacity);
+        self
+    }
+
+    /// Sets the fraction of the width or height of the area that the rotated bounding box of this
+    /// watermark fills.
+    ///
+    /// Per default, the watermark fills 80 percent of the area.
+    pub fn set_fill(&mut self, fill: f32) {
+        self.fill = fill;
+    }
+
+    /// Sets the fraction of the area that this watermark fills and returns the watermark.
+    pub fn with_fill(mut self, fill: f32) -> Self {
+        self.set_fill(fill);
+        self
+    }
+
+    /// Sets the default color of the text of this watermark.
+    pub fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
+    }
+
+    /// Sets the default color of the text of this watermark and returns the watermark.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.set_color(color);
+        self
+    }
+
+    /// Sets the z order of this watermark, see [`PositionedElement::set_z_order`][].
+    ///
+    /// This is only used by [`Document::push_watermark`][].  Per default, the z order is -1, so the
+    /// watermark is drawn behind the document content.
+    ///
+    /// [`PositionedElement::set_z_order`]: ../struct.PositionedElement.html#method.set_z_order
+    /// [`Document::push_watermark`]: ../struct.Document.html#method.push_watermark
+    pub fn set_z_order(&mut self, z_order: i32) {
+        self.z_order = z_order;
+    }
+
+    /// Sets the z order of this watermark and returns the watermark.
+    pub fn with_z_order(mut self, z_order: i32) -> Self {
+        self.set_z_order(z_order);
+        self
+    }
+
+    /// Returns the z order of this watermark.
+    pub fn z_order(&self) -> i32 {
+        self.z_order
+    }
+}
+
+impl Element for Watermark {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        mut style: Style,
+    ) -> Result<RenderResult, Error> {
+        if let Some(color) = self.color {
+            style.set_color(color);
+        }
+        let available = area.size();
+        let size = self.element.measure(context, area.clone(), style)?.size;
+        if size.width <= Mm(0.0) || size.height <= Mm(0.0) {
+            return Ok(RenderResult::default());
+        }
+
+        let rotation = self.rotation.unwrap_or_else(|| {
+            let degrees = f32::from(available.height / f32::from(available.width))
+                .atan()
+                .to_degrees();
+            Rotation::from(-degrees)
+        });
+        let (sin, cos) = rotation.degrees.to_radians().sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+        let bounding_width = size.width * cos + size.height * sin;
+        let bounding_height = size.width * sin + size.height * cos;
+        let scale = f32::from(available.width / f32::from(bounding_width))
+            .min(f32::from(available.height / f32::from(bounding_height)))
+            * self.fill;
+
+        let center = Position::new(available.width / 2.0, available.height / 2.0);
+        let element = &mut self.element;
+        area.with_opacity(self.opacity, |area| {
+            area.with_transform(center, rotation, scale, |mut area| {
+                area.add_offset(Position::new(
+                    center.x - size.width / 2.0,
+                    center.y - size.height / 2.0,
+                ));
+                area.set_size(size);
+                element.render(context, area, style)
+            })
+        })?;
+        self.element.reset();
+        Ok(RenderResult::default())
+    }
+
+    fn reset(&mut self) {
+        self.element.reset();
+    }
+}
+
+/// Adds a frame around the wrapped element.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use rckive_genpdf::elements;
+/// let p = elements::FramedElement::new(
+///     elements::Paragraph::new("text"),
+/// );
+/// ```
+///
+/// Using [`Element::framed`][]:
+/// ```
+/// use rckive_genpdf::{elements, style, Element as _};
+/// let p = elements::Paragraph::new("text").framed(style::LineStyle::new());
+/// ```
+///
+/// [`Element::framed`]: ../trait.Element.html#method.framed
+#[derive(Clone, Debug, Default)]
+pub struct FramedElement<E: Element> {
+    element: E,
+    is_first: bool,
+    line_style: LineStyle,
+    left: bool,
+    right: bool,
+    top: bool,
     bottom: bool,
     background: bool,
     background_style: BackgroundStyle,
@@ -941,6 +2815,11 @@ impl<E: Element> Element for FramedElement<E> {
 
         Ok(result)
     }
+
+    fn reset(&mut self) {
+        self.is_first = true;
+        self.element.reset();
+    }
 }
 
 /// An unordered list of elements with bullet points.
@@ -1041,6 +2920,19 @@ impl Element for UnorderedList {
     ) -> Result<RenderResult, Error> {
         self.layout.render(context, area, style)
     }
+
+    fn reset(&mut self) {
+        self.layout.reset();
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.layout.measure(context, area, style)
+    }
 }
 
 impl Default for UnorderedList {
@@ -1159,6 +3051,19 @@ impl Element for OrderedList {
     ) -> Result<RenderResult, Error> {
         self.layout.render(context, area, style)
     }
+
+    fn reset(&mut self) {
+        self.layout.reset();
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.layout.measure(context, area, style)
+    }
 }
 
 impl Default for OrderedList {
@@ -1256,6 +3161,24 @@ impl<E: Element> Element for BulletPoint<E> {
         }
         Ok(result)
     }
+
+    fn reset(&mut self) {
+        self.bullet_rendered = false;
+        self.element.reset();
+    }
+}
+
+/// The kind of a row of a [`TableLayout`][].
+///
+/// [`TableLayout`]: struct.TableLayout.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RowKind {
+    /// A header row that is repeated at the top of every page.
+    Header,
+    /// A regular row.
+    Body,
+    /// A footer row that is printed at the bottom of every page that the table is broken on.
+    Footer,
 }
 
 /// A decorator for table cells.
@@ -1275,6 +3198,16 @@ pub trait CellDecorator {
         let _ = (num_columns, num_rows);
     }
 
+    /// Prepares the row with the given index before its cells are prepared and decorated.
+    ///
+    /// The kind of the row indicates whether it is a header row, a regular row or a footer row of
+    /// the table.  Header rows are rendered again at the top of every continuation page, and
+    /// footer rows are rendered at the bottom of every page that the table is broken on.  Footer
+    /// rows have the indizes following the last regular row.
+    fn prepare_row(&mut self, row: usize, kind: RowKind) {
+        let _ = (row, kind);
+    }
+
     /// Prepares the cell with the given indizes and returns the area for rendering the cell.
     fn prepare_cell<'p>(
         &self,
@@ -1296,6 +3229,54 @@ pub trait CellDecorator {
         area: render::Area<'_>,
         row_height: Mm,
     ) -> Mm;
+
+    /// Prepares the cell with the given indizes that spans the given number of columns and rows
+    /// and returns the area for rendering the cell.
+    ///
+    /// The given area covers all spanned columns.  The default implementation calls
+    /// [`prepare_cell`][].
+    ///
+    /// [`prepare_cell`]: #method.prepare_cell
+    fn prepare_spanning_cell<'p>(
+        &self,
+        column: usize,
+        row: usize,
+        colspan: usize,
+        rowspan: usize,
+        area: render::Area<'p>,
+    ) -> render::Area<'p> {
+        let _ = (colspan, rowspan);
+        self.prepare_cell(column, row, area)
+    }
+
+    /// Styles the cell with the given indizes that spans the given number of columns and rows and
+    /// returns the total height of the cell.
+    ///
+    /// The given area covers all spanned columns, and the given height is the height of all
+    /// spanned rows minus the space reserved by [`prepare_spanning_cell`][].  The default
+    /// implementation calls [`decorate_cell`][].
+    ///
+    /// [`prepare_spanning_cell`]: #method.prepare_spanning_cell
+    /// [`decorate_cell`]: #tymethod.decorate_cell
+    #[allow(clippy::too_many_arguments)]
+    fn decorate_spanning_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        colspan: usize,
+        rowspan: usize,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm {
+        let _ = (colspan, rowspan);
+        self.decorate_cell(column, row, has_more, area, row_height)
+    }
+
+    /// Resets the state of this decorator when the table is reset, see [`Element::reset`][].
+    ///
+    /// [`Element::reset`]: ../trait.Element.html#method.reset
+    fn reset(&mut self) {}
 }
 
 /// A cell decorator that draws frames around table cells.
@@ -1375,7 +3356,7 @@ impl FrameCellDecorator {
     fn print_bottom(&self, row: usize, has_more: bool) -> bool {
         if has_more {
             self.cont
-        } else if row + 1 == self.num_rows {
+        } else if row + 1 >= self.num_rows {
             self.outer
         } else {
             false
@@ -1389,10 +3370,39 @@ impl CellDecorator for FrameCellDecorator {
         self.num_rows = num_rows;
     }
 
+    fn prepare_row(&mut self, row: usize, kind: RowKind) {
+        // A repeated header starts at the top of a new page, so it gets the outer border.
+        if kind == RowKind::Header && row == 0 {
+            self.last_row = None;
+        }
+    }
+
     fn prepare_cell<'p>(
         &self,
         column: usize,
         row: usize,
+        area: render::Area<'p>,
+    ) -> render::Area<'p> {
+        self.prepare_spanning_cell(column, row, 1, 1, area)
+    }
+
+    fn decorate_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm {
+        self.decorate_spanning_cell(column, row, 1, 1, has_more, area, row_height)
+    }
+
+    fn prepare_spanning_cell<'p>(
+        &self,
+        column: usize,
+        row: usize,
+        colspan: usize,
+        rowspan: usize,
         mut area: render::Area<'p>,
     ) -> render::Area<'p> {
         let margin = self.line_style.thickness();
@@ -1402,12 +3412,12 @@ impl CellDecorator for FrameCellDecorator {
             } else {
                 0.into()
             },
-            if self.print_right(column) {
+            if self.print_right(column + colspan - 1) {
                 margin
             } else {
                 0.into()
             },
-            if self.print_bottom(row, false) {
+            if self.print_bottom(row + rowspan - 1, false) {
                 margin
             } else {
                 0.into()
@@ -1422,18 +3432,20 @@ impl CellDecorator for FrameCellDecorator {
         area
     }
 
-    fn decorate_cell(
+    fn decorate_spanning_cell(
         &mut self,
         column: usize,
         row: usize,
+        colspan: usize,
+        rowspan: usize,
         has_more: bool,
         area: render::Area<'_>,
         row_height: Mm,
     ) -> Mm {
         let print_top = self.print_top(row);
-        let print_bottom = self.print_bottom(row, has_more);
+        let print_bottom = self.print_bottom(row + rowspan - 1, has_more);
         let print_left = self.print_left(column);
-        let print_right = self.print_right(column);
+        let print_right = self.print_right(column + colspan - 1);
 
         let size = area.size();
         let line_offset = self.line_style.thickness() / 2.0;
@@ -1497,12 +3509,115 @@ impl CellDecorator for FrameCellDecorator {
             );
         }
 
-        if column + 1 == self.num_columns {
+        if column + colspan == self.num_columns {
             self.last_row = Some(row);
         }
 
         total_height
     }
+
+    fn reset(&mut self) {
+        self.last_row = None;
+    }
+}
+
+/// A cell of a table layout with its position and span.
+struct TableCell {
+    element: Box<dyn Element>,
+    column: usize,
+    colspan: usize,
+    rowspan: usize,
+}
+
+impl TableCell {
+    fn new(element: Box<dyn Element>, colspan: usize, rowspan: usize) -> TableCell {
+        TableCell {
+            element,
+            column: 0,
+            colspan,
+            rowspan,
+        }
+    }
+}
+
+/// Keeps track of the columns of a table section that are covered by cells from previous rows.
+#[derive(Clone, Debug, Default)]
+struct RowSpans {
+    // The number of following rows that are covered for every column.
+    covered: Vec<usize>,
+}
+
+impl RowSpans {
+    /// Assigns the columns to the given cells of a new row and checks that the row has the
+    /// expected number of columns.
+    fn place(&mut self, cells: &mut [TableCell], num_columns: usize) -> Result<(), Error> {
+        if self.covered.len() != num_columns {
+            self.covered = vec![0; num_columns];
+        }
+        let mut covered = self.covered.clone();
+        let mut rowspans = vec![0; num_columns];
+        let mut column = 0;
+        for cell in cells.iter_mut() {
+            if cell.colspan == 0 || cell.rowspan == 0 {
+                return Err(Error::new(
+                    "Table cells must span at least one column and one row",
+                    ErrorKind::InvalidData,
+                ));
+            }
+            while column < num_columns && covered[column] > 0 {
+                column += 1;
+            }
+            let end = column + cell.colspan;
+            if end > num_columns || covered[column..end].iter().any(|rows| *rows > 0) {
+                return Err(Error::new(
+                    format!(
+                        "Expected {} columns in table row, received more",
+                        num_columns
+                    ),
+                    ErrorKind::InvalidData,
+                ));
+            }
+            cell.column = column;
+            rowspans[column..end].fill(cell.rowspan);
+            column = end;
+        }
+
+        let received = covered
+            .iter()
+            .zip(&rowspans)
+            .filter(|(covered, rowspan)| **covered > 0 || **rowspan > 0)
+            .count();
+        if received != num_columns {
+            return Err(Error::new(
+                format!(
+                    "Expected {} columns in table row, received {}",
+                    num_columns, received
+                ),
+                ErrorKind::InvalidData,
+            ));
+        }
+
+        for (covered, rowspan) in covered.iter_mut().zip(rowspans) {
+            *covered = if rowspan > 0 {
+                rowspan - 1
+            } else {
+                *covered - 1
+            };
+        }
+        self.covered = covered;
+        Ok(())
+    }
+}
+
+/// A cell that has been rendered by a table layout but not yet decorated.
+struct RenderedCell<'p> {
+    row: usize,
+    column: usize,
+    colspan: usize,
+    rowspan: usize,
+    area: render::Area<'p>,
+    inset: Mm,
+    height: Mm,
 }
 
 /// A row of a table layout.
@@ -1511,6 +3626,10 @@ impl CellDecorator for FrameCellDecorator {
 /// to the row using [`push_element`][] or [`element`][], you can append the row to the table
 /// layout by calling [`push`][].
 ///
+/// Cells that span several columns or rows can be added with [`push_element_spanning`][] or
+/// [`element_spanning`][].  Cells that are covered by a cell spanning several rows are skipped in
+/// the following rows.
+///
 /// # Examples
 ///
 /// With setters:
@@ -1534,26 +3653,49 @@ impl CellDecorator for FrameCellDecorator {
 ///     .expect("Invalid table row");
 /// ```
 ///
+/// With spanning cells:
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut table = elements::TableLayout::new(vec![2, 1, 1, 1, 1]);
+/// table
+///     .row()
+///     .element_spanning(elements::Paragraph::new("Region"), 1, 2)
+///     .element_spanning(elements::Paragraph::new("Q1"), 2, 1)
+///     .element_spanning(elements::Paragraph::new("Q2"), 2, 1)
+///     .push_header()
+///     .expect("Invalid table row");
+/// table
+///     .row()
+///     .element(elements::Paragraph::new("Jan"))
+///     .element(elements::Paragraph::new("Feb"))
+///     .element(elements::Paragraph::new("Apr"))
+///     .element(elements::Paragraph::new("May"))
+///     .push_header()
+///     .expect("Invalid table row");
+/// ```
+///
 /// [`TableLayout`]: struct.TableLayout.html
 /// [`push`]: #method.push
 /// [`push_element`]: #method.push_element
 /// [`element`]: #method.element
+/// [`push_element_spanning`]: #method.push_element_spanning
+/// [`element_spanning`]: #method.element_spanning
 pub struct TableLayoutRow<'a> {
     table_layout: &'a mut TableLayout,
-    elements: Vec<Box<dyn Element>>,
+    cells: Vec<TableCell>,
 }
 
 impl<'a> TableLayoutRow<'a> {
     fn new(table_layout: &'a mut TableLayout) -> TableLayoutRow<'a> {
         TableLayoutRow {
             table_layout,
-            elements: Vec::new(),
+            cells: Vec::new(),
         }
     }
 
     /// Adds the given element to this row.
     pub fn push_element<E: IntoBoxedElement>(&mut self, element: E) {
-        self.elements.push(element.into_boxed_element());
+        self.push_element_spanning(element, 1, 1);
     }
 
     /// Adds the given element to this row and returns the row.
@@ -1563,19 +3705,64 @@ impl<'a> TableLayoutRow<'a> {
         self
     }
 
+    /// Adds the given element to this row as a cell that spans the given number of columns and
+    /// rows.
+    pub fn push_element_spanning<E: IntoBoxedElement>(
+        &mut self,
+        element: E,
+        colspan: usize,
+        rowspan: usize,
+    ) {
+        self.cells.push(TableCell::new(
+            element.into_boxed_element(),
+            colspan,
+            rowspan,
+        ));
+    }
+
+    /// Adds the given element to this row as a cell that spans the given number of columns and
+    /// rows and returns the row.
+    #[must_use]
+    pub fn element_spanning<E: IntoBoxedElement>(
+        mut self,
+        element: E,
+        colspan: usize,
+        rowspan: usize,
+    ) -> Self {
+        self.push_element_spanning(element, colspan, rowspan);
+        self
+    }
+
     /// Tries to append this row to the table.
     ///
-    /// This method fails if the number of elements in this row does not match the number of
-    /// columns in the table.
+    /// This method fails if the number of columns covered by this row does not match the number
+    /// of columns in the table.
     pub fn push(self) -> Result<(), Error> {
-        self.table_layout.push_row(self.elements)
+        self.table_layout.push_cells(self.cells, RowKind::Body)
+    }
+
+    /// Tries to append this row to the header rows of the table, see
+    /// [`TableLayout::push_header_row`][].
+    ///
+    /// [`TableLayout::push_header_row`]: struct.TableLayout.html#method.push_header_row
+    pub fn push_header(self) -> Result<(), Error> {
+        self.table_layout.push_cells(self.cells, RowKind::Header)
+    }
+
+    /// Tries to append this row to the footer rows of the table, see
+    /// [`TableLayout::push_footer_row`][].
+    ///
+    /// [`TableLayout::push_footer_row`]: struct.TableLayout.html#method.push_footer_row
+    pub fn push_footer(self) -> Result<(), Error> {
+        self.table_layout.push_cells(self.cells, RowKind::Footer)
     }
 }
 
 impl<'a, E: IntoBoxedElement> iter::Extend<E> for TableLayoutRow<'a> {
     fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
-        self.elements
-            .extend(iter.into_iter().map(|e| e.into_boxed_element()))
+        for element in iter {
+            self.push_element(element);
+        }
     }
 }
 
@@ -1589,6 +3776,15 @@ impl<'a, E: IntoBoxedElement> iter::Extend<E> for TableLayoutRow<'a> {
 /// The column widths are determined by the weights that have been set in the constructor.  The
 /// table always uses the full width of the provided area.
 ///
+/// A cell can span several columns and rows, see [`TableLayoutRow::push_element_spanning`][].
+/// Rows that are connected by cells spanning several rows are kept together on one page.
+///
+/// If the table does not fit on one page, it is continued on the next page.  The first rows of the
+/// table can be marked as header rows using [`set_header_rows`][] or [`push_header_row`][].  They
+/// are rendered again at the top of every continuation page.  Footer rows added with
+/// [`push_footer_row`][] are only rendered at the bottom of every page that the table is broken
+/// on, for example to print a “continued on next page” note.
+///
 /// # Examples
 ///
 /// With setters:
@@ -1613,12 +3809,44 @@ impl<'a, E: IntoBoxedElement> iter::Extend<E> for TableLayoutRow<'a> {
 ///     .expect("Invalid table row");
 /// ```
 ///
+/// With header and footer rows:
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut table = elements::TableLayout::new(vec![1, 1]);
+/// table
+///     .row()
+///     .element(elements::Text::new("Name"))
+///     .element(elements::Text::new("Value"))
+///     .push_header()
+///     .expect("Invalid table row");
+/// table
+///     .row()
+///     .element(elements::Text::new("continued on next page"))
+///     .element(elements::Text::new(""))
+///     .push_footer()
+///     .expect("Invalid table row");
+/// ```
+///
 /// [`CellDecorator`]: trait.CellDecorator.html
 /// [`FrameCellDecorator`]: struct.FrameCellDecorator.html
+/// [`TableLayoutRow::push_element_spanning`]: struct.TableLayoutRow.html#method.push_element_spanning
+/// [`set_header_rows`]: #method.set_header_rows
+/// [`push_header_row`]: #method.push_header_row
+/// [`push_footer_row`]: #method.push_footer_row
 pub struct TableLayout {
     column_weights: Vec<usize>,
-    rows: Vec<Vec<Box<dyn Element>>>,
+    rows: Vec<Vec<TableCell>>,
+    header_rows: usize,
+    footer_rows: Vec<Vec<TableCell>>,
+    header_spans: RowSpans,
+    body_spans: RowSpans,
+    footer_spans: RowSpans,
     render_idx: usize,
+    is_first: bool,
+    // Whether the row at render_idx has been rendered partially
+    is_row_started: bool,
+    // Whether a part of the table has been rendered since the last reset
+    is_started: bool,
     cell_decorator: Option<Box<dyn CellDecorator>>,
 }
 
@@ -1631,7 +3859,15 @@ impl TableLayout {
         TableLayout {
             column_weights,
             rows: Vec::new(),
+            header_rows: 0,
+            footer_rows: Vec::new(),
+            header_spans: RowSpans::default(),
+            body_spans: RowSpans::default(),
+            footer_spans: RowSpans::default(),
             render_idx: 0,
+            is_first: true,
+            is_row_started: false,
+            is_started: false,
             cell_decorator: None,
         }
     }
@@ -1641,6 +3877,20 @@ impl TableLayout {
         self.cell_decorator = Some(Box::from(decorator));
     }
 
+    /// Marks the first rows of this table as header rows.
+    ///
+    /// The header rows are rendered again at the top of every page that the table is continued
+    /// on.  They are only rendered if at least a part of the following row fits below them.
+    pub fn set_header_rows(&mut self, header_rows: usize) {
+        self.header_rows = header_rows;
+    }
+
+    /// Marks the first rows of this table as header rows and returns the table.
+    pub fn with_header_rows(mut self, header_rows: usize) -> Self {
+        self.set_header_rows(header_rows);
+        self
+    }
+
     /// Adds a row to this table using the [`TableLayoutRow`][] helper struct.
     ///
     /// [`TableLayoutRow`]: struct.TableLayoutRow.html
@@ -1650,61 +3900,258 @@ impl TableLayout {
 
     /// Adds a row to this table.
     ///
-    /// The number of elements in the given vector must match the number of columns.  Otherwise, an
-    /// error is returned.
+    /// The number of elements in the given vector must match the number of columns that are not
+    /// covered by cells spanning several rows.  Otherwise, an error is returned.
     pub fn push_row(&mut self, row: Vec<Box<dyn Element>>) -> Result<(), Error> {
-        if row.len() == self.column_weights.len() {
-            self.rows.push(row);
-            Ok(())
+        self.push_cells(Self::cells(row), RowKind::Body)
+    }
+
+    /// Adds a header row to this table.
+    ///
+    /// The row is inserted after the existing header rows, see [`set_header_rows`][].  The number
+    /// of elements in the given vector must match the number of columns.  Otherwise, an error is
+    /// returned.
+    ///
+    /// [`set_header_rows`]: #method.set_header_rows
+    pub fn push_header_row(&mut self, row: Vec<Box<dyn Element>>) -> Result<(), Error> {
+        self.push_cells(Self::cells(row), RowKind::Header)
+    }
+
+    /// Adds a footer row to this table.
+    ///
+    /// Footer rows are rendered at the bottom of every page that the table is broken on, but not
+    /// after the last row of the table.  The number of elements in the given vector must match the
+    /// number of columns.  Otherwise, an error is returned.
+    pub fn push_footer_row(&mut self, row: Vec<Box<dyn Element>>) -> Result<(), Error> {
+        self.push_cells(Self::cells(row), RowKind::Footer)
+    }
+
+    fn cells(row: Vec<Box<dyn Element>>) -> Vec<TableCell> {
+        row.into_iter()
+            .map(|element| TableCell::new(element, 1, 1))
+            .collect()
+    }
+
+    fn push_cells(&mut self, mut cells: Vec<TableCell>, kind: RowKind) -> Result<(), Error> {
+        let num_columns = self.column_weights.len();
+        match kind {
+            RowKind::Header => {
+                self.header_spans.place(&mut cells, num_columns)?;
+                let idx = self.header_rows.min(self.rows.len());
+                self.rows.insert(idx, cells);
+                self.header_rows = idx + 1;
+            }
+            RowKind::Body => {
+                self.body_spans.place(&mut cells, num_columns)?;
+                self.rows.push(cells);
+            }
+            RowKind::Footer => {
+                self.footer_spans.place(&mut cells, num_columns)?;
+                self.footer_rows.push(cells);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the cells of the row with the given index.  The footer rows have the indizes
+    /// following the regular rows.
+    fn row_cells(&mut self, row: usize) -> &mut Vec<TableCell> {
+        if row < self.rows.len() {
+            &mut self.rows[row]
         } else {
-            Err(Error::new(
-                format!(
-                    "Expected {} elements in table row, received {}",
-                    self.column_weights.len(),
-                    row.len()
-                ),
-                ErrorKind::InvalidData,
-            ))
+            &mut self.footer_rows[row - self.rows.len()]
+        }
+    }
+
+    fn reset_rows(&mut self, start: usize, end: usize) {
+        for row in start..end {
+            for cell in self.row_cells(row) {
+                cell.element.reset();
+            }
         }
     }
 
-    fn render_row(
+    /// Returns the end of the group of rows starting at the given row that are connected by cells
+    /// spanning several rows.
+    fn group_end(&self, start: usize) -> usize {
+        let mut end = start + 1;
+        let mut row = start;
+        while row < end && row < self.rows.len() {
+            for cell in &self.rows[row] {
+                end = end.max(row + cell.rowspan);
+            }
+            row += 1;
+        }
+        end.min(self.rows.len())
+    }
+
+    /// Renders the rows `start..end` as one group.
+    ///
+    /// If `measure` is set, the rows are rendered to a measuring area and reset afterwards, and
+    /// the cell decorator is not called.  Cells spanning several rows are cut off at the end of
+    /// the group.  The width of the returned size is the width of the area if at least one cell
+    /// has rendered some content, and zero otherwise.
+    #[allow(clippy::too_many_arguments)]
+    fn render_rows(
         &mut self,
         context: &Context,
         area: render::Area<'_>,
         style: Style,
+        start: usize,
+        end: usize,
+        kind: RowKind,
+        measure: bool,
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
+        let area = if measure { area.to_measuring() } else { area };
 
-        let areas = area.split_horizontally(&self.column_weights);
-        let cell_areas = if let Some(decorator) = &self.cell_decorator {
-            areas
-                .iter()
-                .enumerate()
-                .map(|(i, area)| decorator.prepare_cell(i, self.render_idx, area.clone()))
-                .collect()
-        } else {
-            areas.clone()
-        };
+        if !measure {
+            if let Some(decorator) = &mut self.cell_decorator {
+                for row in start..end {
+                    decorator.prepare_row(row, kind);
+                }
+            }
+        }
+
+        let columns = area.split_horizontally(&self.column_weights);
+        let mut heights = vec![Mm::from(0); end - start];
+        let mut cells = Vec::new();
+        let mut y = Mm::from(0);
+        for row in start..end {
+            let decorator = &self.cell_decorator;
+            let row_cells = if row < self.rows.len() {
+                &mut self.rows[row]
+            } else {
+                &mut self.footer_rows[row - self.rows.len()]
+            };
+            for cell in row_cells.iter_mut() {
+                let rowspan = cell.rowspan.min(end - row);
+                let mut cell_area = columns[cell.column].clone();
+                cell_area.add_offset(Position::new(0, y));
+                cell_area.set_width(
+                    columns[cell.column..cell.column + cell.colspan]
+                        .iter()
+                        .map(|area| area.size().width)
+                        .sum(),
+                );
+                let content_area = if let Some(decorator) = decorator {
+                    decorator.prepare_spanning_cell(
+                        cell.column,
+                        row,
+                        cell.colspan,
+                        rowspan,
+                        cell_area.clone(),
+                    )
+                } else {
+                    cell_area.clone()
+                };
+                let inset = cell_area.size().height - content_area.size().height;
 
-        let mut row_height = Mm::from(0);
-        for (area, element) in cell_areas.iter().zip(self.rows[self.render_idx].iter_mut()) {
-            let element_result = element.render(context, area.clone(), style)?;
-            result.has_more |= element_result.has_more;
-            row_height = row_height.max(element_result.size.height);
+                let element_result = cell.element.render(context, content_area, style)?;
+                if measure {
+                    cell.element.reset();
+                }
+                result.has_more |= element_result.has_more;
+                cells.push(RenderedCell {
+                    row,
+                    column: cell.column,
+                    colspan: cell.colspan,
+                    rowspan,
+                    area: cell_area,
+                    inset,
+                    height: element_result.size.height,
+                });
+            }
+
+            // The row has to be high enough for all cells that end in this row.
+            let mut height = Mm::from(0);
+            for cell in cells
+                .iter()
+                .filter(|cell| cell.row + cell.rowspan == row + 1)
+            {
+                let above: Mm = heights[cell.row - start..row - start].iter().copied().sum();
+                height = height.max(cell.height + cell.inset - above);
+            }
+            heights[row - start] = height;
+            y += height;
+        }
+        result.size.height = y;
+        if cells.iter().any(|cell| cell.height > Mm::from(0)) {
+            result.size.width = area.size().width;
         }
-        result.size.height = row_height;
 
-        if let Some(decorator) = &mut self.cell_decorator {
-            for (i, area) in areas.into_iter().enumerate() {
-                let height =
-                    decorator.decorate_cell(i, self.render_idx, result.has_more, area, row_height);
-                result.size.height = result.size.height.max(height);
+        if !measure {
+            if let Some(decorator) = &mut self.cell_decorator {
+                for cell in cells {
+                    let first = cell.row - start;
+                    let height: Mm = heights[first..first + cell.rowspan].iter().copied().sum();
+                    let decorated_height = decorator.decorate_spanning_cell(
+                        cell.column,
+                        cell.row,
+                        cell.colspan,
+                        cell.rowspan,
+                        result.has_more,
+                        cell.area,
+                        height - cell.inset,
+                    );
+                    if end - start == 1 {
+                        result.size.height = result.size.height.max(decorated_height);
+                    }
+                }
             }
         }
 
         Ok(result)
     }
+
+    /// Returns `true` if at least a part of the row group starting at the given row fits into the
+    /// given area.  Groups of several rows have to fit completely.
+    ///
+    /// A row that has been rendered partially cannot be measured without restarting it, so it is
+    /// assumed to fit.
+    fn group_fits(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+        start: usize,
+    ) -> Result<bool, Error> {
+        if start == self.render_idx && self.is_row_started {
+            return Ok(true);
+        }
+        let end = self.group_end(start);
+        let result = self.render_rows(context, area, style, start, end, RowKind::Body, true)?;
+        Ok(!result.has_more || (end - start == 1 && result.size.width > Mm::from(0)))
+    }
+
+    /// Returns `true` if all rows starting at the given row fit into the given area.
+    ///
+    /// The row groups are measured one after another until a group does not fit, so at most one
+    /// area full of rows is measured.  A row that has been rendered partially cannot be measured
+    /// without restarting it, so it is assumed not to fit.
+    fn rows_fit(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+        start: usize,
+    ) -> Result<bool, Error> {
+        if start == self.render_idx && self.is_row_started {
+            return Ok(false);
+        }
+        let mut row = start;
+        while row < self.rows.len() {
+            let end = self.group_end(row);
+            let result =
+                self.render_rows(context, area.clone(), style, row, end, RowKind::Body, true)?;
+            if result.has_more {
+                return Ok(false);
+            }
+            area.add_content_offset(result.size.height);
+            row = end;
+        }
+        Ok(true)
+    }
 }
 
 impl Element for TableLayout {
@@ -1722,16 +4169,1180 @@ impl Element for TableLayout {
             decorator.set_table_size(self.column_weights.len(), self.rows.len());
         }
         result.size.width = area.size().width;
+
+        // The header rows are rendered at the top of the table and repeated if the table is
+        // continued on a new page, but only if at least a part of the next row fits below them.
+        let header_rows = self.header_rows.min(self.rows.len());
+        let is_repeated = !self.is_first && self.render_idx >= header_rows;
+        let mut header_height = None;
+        if header_rows > 0 && (self.render_idx == 0 || is_repeated) {
+            let header_result = self.render_rows(
+                context,
+                area.clone(),
+                style,
+                0,
+                header_rows,
+                RowKind::Header,
+                true,
+            )?;
+            if !header_result.has_more {
+                header_height = Some(header_result.size.height);
+            } else if is_repeated {
+                return Ok(RenderResult {
+                    has_more: true,
+                    ..Default::default()
+                });
+            }
+        }
+        self.is_first = false;
+        let mut body_area = area.clone();
+        if let Some(height) = header_height {
+            body_area.add_offset(Position::new(0, height));
+        }
+        let body_start = if header_height.is_some() {
+            self.render_idx.max(header_rows)
+        } else {
+            self.render_idx
+        };
+
+        // Reserve the space for the footer rows if the table does not fit into the area.
+        let footer_start = self.rows.len();
+        let footer_end = footer_start + self.footer_rows.len();
+        let has_footer = footer_end > footer_start
+            && !self.rows_fit(context, body_area.clone(), style, body_start)?;
+        if has_footer {
+            let footer_result = self.render_rows(
+                context,
+                area.clone(),
+                style,
+                footer_start,
+                footer_end,
+                RowKind::Footer,
+                true,
+            )?;
+            let height = body_area.size().height - footer_result.size.height;
+            body_area.set_height(height.max(Mm::from(0)));
+        }
+
+        if let Some(height) = header_height {
+            if body_start < self.rows.len()
+                && !self.group_fits(context, body_area.clone(), style, body_start)?
+            {
+                // Not even a part of the next row fits below the header.
+                return Ok(RenderResult {
+                    has_more: true,
+                    ..Default::default()
+                });
+            }
+            self.reset_rows(0, header_rows);
+            let header_result = self.render_rows(
+                context,
+                area.clone(),
+                style,
+                0,
+                header_rows,
+                RowKind::Header,
+                false,
+            )?;
+            self.reset_rows(0, header_rows);
+            result.size.height += header_result.size.height;
+            area.add_offset(Position::new(0, header_result.size.height));
+            // The cell decorator can change the height of the header rows.
+            body_area.add_offset(Position::new(0, header_result.size.height - height));
+            self.render_idx = body_start;
+        }
+
+        let mut body_height = Mm::from(0);
         while self.render_idx < self.rows.len() {
-            let row_result = self.render_row(context, area.clone(), style)?;
-            result.size.height += row_result.size.height;
-            area.add_offset(Position::new(0, row_result.size.height));
-            if row_result.has_more {
+            let start = self.render_idx;
+            let end = self.group_end(start);
+            let kind = if start < header_rows {
+                RowKind::Header
+            } else {
+                RowKind::Body
+            };
+            if end - start > 1 {
+                // Rows connected by cells spanning several rows are kept together.
+                let measure_result =
+                    self.render_rows(context, body_area.clone(), style, start, end, kind, true)?;
+                if measure_result.has_more {
+                    break;
+                }
+            }
+            let row_result =
+                self.render_rows(context, body_area.clone(), style, start, end, kind, false)?;
+            body_height += row_result.size.height;
+            body_area.add_content_offset(row_result.size.height);
+            if row_result.has_more && end - start == 1 {
+                self.is_row_started |= row_result.size.width > Mm::from(0);
                 break;
             }
-            self.render_idx += 1;
+            self.render_idx = end;
+            self.is_row_started = false;
         }
+        result.size.height += body_height;
         result.has_more = self.render_idx < self.rows.len();
+
+        if result.has_more && has_footer {
+            area.add_offset(Position::new(0, body_height));
+            let footer_result = self.render_rows(
+                context,
+                area,
+                style,
+                footer_start,
+                footer_end,
+                RowKind::Footer,
+                false,
+            )?;
+            self.reset_rows(footer_start, footer_end);
+            result.size.height += footer_result.size.height;
+        }
+        if result.size.height == Mm::from(0) {
+            result.size.width = Mm::from(0);
+        } else {
+            self.is_started = true;
+        }
         Ok(result)
     }
+
+    fn reset(&mut self) {
+        self.render_idx = 0;
+        self.is_first = true;
+        self.is_row_started = false;
+        self.is_started = false;
+        for row in self.rows.iter_mut().chain(self.footer_rows.iter_mut()) {
+            for cell in row {
+                cell.element.reset();
+            }
+        }
+        if let Some(decorator) = &mut self.cell_decorator {
+            decorator.reset();
+        }
+    }
+
+    /// Measures this table and resets it afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a part of the table has already been rendered.
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.is_started {
+            return Err(Error::new(
+                "A table cannot be measured after it has been rendered partially",
+                ErrorKind::Internal,
+            ));
+        }
+        crate::measure_and_reset(self, context, area, style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Break, ColumnWidth, Columns, Float, Heading, KeepTogether, LinearLayout, PageBreak,
+        Paragraph, TableLayout, TableOfContents, Text,
+    };
+    use crate::style::{Link, Style};
+    use crate::{
+        fonts, render, Context, Document, Element as _, Margins, Mm, Size, VerticalAlignment,
+    };
+    use float_cmp::approx_eq;
+
+    fn get_context() -> Context {
+        let font_family = fonts::from_files(
+            "./tests/files/liberation",
+            "LiberationSans",
+            Some(fonts::Builtin::Helvetica),
+        )
+        .expect("Failed to load the default font family");
+        Context::new(fonts::FontCache::new(font_family))
+    }
+
+    #[test]
+    fn test_horizontal_layout_widths() {
+        let context = get_context();
+        let style = Style::new();
+        let text_width = style.str_width(&context.font_cache, "Text");
+
+        let mut layout = LinearLayout::horizontal()
+            .element_with_width(Break::new(1.0), ColumnWidth::Fixed(Mm::from(20)))
+            .element_with_width(Text::new("Text"), ColumnWidth::Content)
+            .element_with_width(Break::new(1.0), ColumnWidth::Weight(1))
+            .element_with_width(Break::new(1.0), ColumnWidth::Weight(3));
+        let area = render::Area::measuring(Size::new(100, 100));
+        let result = layout.render(&context, area, style).unwrap();
+        assert!(!result.has_more);
+        assert!(approx_eq!(Mm, Mm::from(100), result.size.width, ulps = 10));
+
+        let remaining = Mm::from(80) - text_width;
+        assert!(approx_eq!(
+            Mm,
+            text_width,
+            layout.column_widths[1],
+            ulps = 10
+        ));
+        assert!(approx_eq!(
+            Mm,
+            remaining / 4.0,
+            layout.column_widths[2],
+            ulps = 10
+        ));
+        assert!(approx_eq!(
+            Mm,
+            remaining * 0.75,
+            layout.column_widths[3],
+            ulps = 10
+        ));
+    }
+
+    #[test]
+    fn test_horizontal_layout_fixed_widths() {
+        let context = get_context();
+        let style = Style::new();
+
+        // The fixed widths are clamped to the width of the area.
+        let mut layout = LinearLayout::horizontal()
+            .element_with_width(Break::new(1.0), ColumnWidth::Fixed(Mm::from(60)))
+            .element_with_width(Break::new(1.0), ColumnWidth::Fixed(Mm::from(60)))
+            .element_with_width(Break::new(1.0), ColumnWidth::Weight(1));
+        let area = render::Area::measuring(Size::new(100, 100));
+        let result = layout.render(&context, area, style).unwrap();
+        assert!(!result.has_more);
+        assert_eq!(
+            vec![Mm::from(60), Mm::from(40), Mm::from(0)],
+            layout.column_widths
+        );
+        assert!(approx_eq!(Mm, Mm::from(100), result.size.width, ulps = 10));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_horizontal_layout_orphan() {
+        let context = get_context();
+        let style = Style::new();
+
+        // An orphan layout is rendered at its position without using space in the area.
+        let mut layout = LinearLayout::horizontal()
+            .element(Text::new("1"))
+            .element(Text::new("2"))
+            .with_orphan(true)
+            .with_position(10, 200);
+        let area = render::Area::measuring(Size::new(100, 100));
+        let result = layout.render(&context, area, style).unwrap();
+        assert!(!result.has_more);
+        assert_eq!(Size::new(0, 0), result.size);
+    }
+
+    #[test]
+    fn test_horizontal_layout_continuation() {
+        let context = get_context();
+        let style = Style::new();
+        let line_height = style.line_height(&context.font_cache);
+
+        let mut layout = LinearLayout::horizontal()
+            .element(Text::new("1"))
+            .element(
+                LinearLayout::vertical()
+                    .element(Text::new("2"))
+                    .element(Text::new("3"))
+                    .element(Text::new("4")),
+            )
+            .with_vertical_alignment(VerticalAlignment::Bottom);
+        let area = render::Area::measuring(Size::new(100, line_height * 2.0));
+        let result = layout.render(&context, area.clone(), style).unwrap();
+        assert!(result.has_more);
+        assert!(approx_eq!(
+            Mm,
+            line_height * 2.0,
+            result.size.height,
+            ulps = 10
+        ));
+
+        let result = layout.render(&context, area, style).unwrap();
+        assert!(!result.has_more);
+        assert!(approx_eq!(Mm, line_height, result.size.height, ulps = 10));
+    }
+
+    #[test]
+    fn test_table_header_and_footer_rows() {
+        let mut doc = get_document();
+        let line_height = Style::new().line_height(doc.font_cache());
+        // Each page has room for the header, two rows and the footer, or for the header and three
+        // rows if the table ends on the page.
+        doc.set_paper_size(Size::new(100, line_height * 4.5));
+
+        let mut table = TableLayout::new(vec![1]);
+        table
+            .row()
+            .element(Text::new("Header"))
+            .push_header()
+            .unwrap();
+        table
+            .row()
+            .element(Text::new("continued"))
+            .push_footer()
+            .unwrap();
+        for i in 0..5 {
+            table
+                .row()
+                .element(Text::new(format!("Row{}", i)))
+                .push()
+                .unwrap();
+        }
+        doc.push(table);
+
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load document");
+        let pages: Vec<_> = (1..=pdf.get_pages().len() as u32)
+            .map(|page| pdf.extract_text(&[page]).unwrap().replace(['\n', ' '], ""))
+            .collect();
+        assert_eq!(vec!["HeaderRow0Row1continued", "HeaderRow2Row3Row4"], pages);
+    }
+
+    #[test]
+    fn test_table_row_taller_than_page() {
+        let mut doc = get_document();
+        let line_height = Style::new().line_height(doc.font_cache());
+        doc.set_paper_size(Size::new(100, line_height * 4.5));
+
+        let mut lines = LinearLayout::vertical();
+        for i in 0..6 {
+            lines.push(Text::new(format!("L{}", i)));
+        }
+        let mut table = TableLayout::new(vec![1]);
+        table
+            .row()
+            .element(Text::new("Header"))
+            .push_header()
+            .unwrap();
+        table
+            .row()
+            .element(Text::new("continued"))
+            .push_footer()
+            .unwrap();
+        table.row().element(lines).push().unwrap();
+        doc.push(table);
+
+        // The partially rendered row is continued below the repeated header instead of being
+        // measured and restarted.
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load document");
+        let pages: Vec<_> = (1..=pdf.get_pages().len() as u32)
+            .map(|page| pdf.extract_text(&[page]).unwrap().replace(['\n', ' '], ""))
+            .collect();
+        assert_eq!(
+            vec!["HeaderL0L1continued", "HeaderL2L3continued", "HeaderL4L5"],
+            pages
+        );
+    }
+
+    #[test]
+    fn test_table_header_without_rows() {
+        use super::{CellDecorator, RowKind};
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        struct HeaderCounter(Rc<Cell<usize>>);
+
+        impl CellDecorator for HeaderCounter {
+            fn prepare_row(&mut self, _row: usize, kind: RowKind) {
+                if kind == RowKind::Header {
+                    self.0.set(self.0.get() + 1);
+                }
+            }
+
+            fn decorate_cell(
+                &mut self,
+                _column: usize,
+                _row: usize,
+                _has_more: bool,
+                _area: render::Area<'_>,
+                row_height: Mm,
+            ) -> Mm {
+                row_height
+            }
+        }
+
+        let context = get_context();
+        let style = Style::new();
+        let line_height = style.line_height(&context.font_cache);
+        let headers = Rc::new(Cell::new(0));
+        let mut table = TableLayout::new(vec![1]);
+        table.set_cell_decorator(HeaderCounter(headers.clone()));
+        table
+            .row()
+            .element(Text::new("Header"))
+            .push_header()
+            .unwrap();
+        for i in 0..2 {
+            table
+                .row()
+                .element(Text::new(format!("Row{}", i)))
+                .push()
+                .unwrap();
+        }
+
+        let mut render = |lines: f32| {
+            let area = render::Area::measuring(Size::new(100, line_height * lines));
+            table.render(&context, area, style).unwrap()
+        };
+        // The header only fits if the first row fits too.
+        let result = render(1.5);
+        assert!(result.has_more);
+        assert_eq!(Size::new(0, 0), result.size);
+        assert_eq!(0, headers.get());
+
+        let result = render(2.5);
+        assert!(result.has_more);
+        assert_eq!(1, headers.get());
+
+        // The header is not repeated if the next row does not fit below it.
+        let result = render(1.5);
+        assert!(result.has_more);
+        assert_eq!(Size::new(0, 0), result.size);
+        assert_eq!(1, headers.get());
+
+        let result = render(2.5);
+        assert!(!result.has_more);
+        assert_eq!(2, headers.get());
+    }
+
+    #[test]
+    fn test_table_spans() {
+        let mut table = TableLayout::new(vec![1, 1, 1]);
+        // A cell spanning more columns than the table has is rejected.
+        assert!(table
+            .row()
+            .element_spanning(Text::new("A"), 4, 1)
+            .push()
+            .is_err());
+        table
+            .row()
+            .element_spanning(Text::new("A"), 1, 2)
+            .element_spanning(Text::new("B"), 2, 1)
+            .push()
+            .unwrap();
+        // The first column is covered by the cell spanning two rows.
+        assert!(table
+            .row()
+            .element(Text::new("C"))
+            .element(Text::new("D"))
+            .element(Text::new("E"))
+            .push()
+            .is_err());
+        table
+            .row()
+            .element(Text::new("C"))
+            .element(Text::new("D"))
+            .push()
+            .unwrap();
+        table
+            .row()
+            .element_spanning(Text::new("F"), 3, 1)
+            .push()
+            .unwrap();
+        assert_eq!(2, table.group_end(0));
+        assert_eq!(2, table.group_end(1));
+        assert_eq!(3, table.group_end(2));
+
+        let context = get_context();
+        let line_height = Style::new().line_height(&context.font_cache);
+        let area = render::Area::measuring(Size::new(90, 200));
+        let result = table.render(&context, area, Style::new()).unwrap();
+        assert!(!result.has_more);
+        assert_eq!(line_height * 3.0, result.size.height);
+
+        // The rows connected by the spanning cell are moved to the next page together.
+        table.reset();
+        let area = render::Area::measuring(Size::new(90, line_height * 1.5));
+        let result = table.render(&context, area, Style::new()).unwrap();
+        assert!(result.has_more);
+        assert_eq!(Mm::from(0), result.size.height);
+    }
+
+    fn get_document() -> Document {
+        let font_family = fonts::from_files(
+            "./tests/files/liberation",
+            "LiberationSans",
+            Some(fonts::Builtin::Helvetica),
+        )
+        .expect("Failed to load the default font family");
+        Document::new(font_family)
+    }
+
+    #[test]
+    fn test_justified_word_spacing() {
+        use crate::style::StyledCow;
+        use crate::Alignment;
+
+        let context = get_context();
+        let line = vec![
+            StyledCow::new("Lorem ipsum ", Style::new()),
+            StyledCow::new("dolor  ", Style::new().bold()),
+        ];
+        let width: Mm = ["Lorem ipsum ", "dolor"]
+            .iter()
+            .zip([Style::new(), Style::new().bold()])
+            .map(|(s, style)| style.str_width(&context.font_cache, s))
+            .sum();
+
+        let mut paragraph = Paragraph::default().aligned(Alignment::Justified);
+        let mut justified = line.clone();
+        let spacing = paragraph.get_word_spacing(&context, &mut justified, false, width + Mm(4.0));
+        assert_eq!(Mm(2.0), spacing);
+        let parts: Vec<_> = justified.iter().map(|s| s.s.as_ref()).collect();
+        assert_eq!(vec!["Lorem ipsum ", "dolor"], parts);
+
+        let mut last = line.clone();
+        let spacing = paragraph.get_word_spacing(&context, &mut last, true, width + Mm(4.0));
+        assert_eq!(Mm(0.0), spacing);
+        assert_eq!("dolor  ", last[1].s);
+
+        paragraph.set_justify_last_line(true);
+        let spacing = paragraph.get_word_spacing(&context, &mut last, true, width + Mm(4.0));
+        assert_eq!(Mm(2.0), spacing);
+    }
+
+    #[test]
+    fn test_optimal_line_breaking() {
+        use crate::style::{StyledStr, StyledString};
+        use crate::{wrap, LineBreaking};
+
+        let context = get_context();
+        let text = "In olden times when wishing still helped one, there lived a king whose \
+                    daughters were all beautiful, but the youngest was so beautiful that the sun \
+                    itself, which has seen so much, was astonished whenever it shone in her face.";
+        let words: Vec<StyledString> = wrap::Words::new(vec![StyledString::from(text)]).collect();
+        let width = Mm(90.0);
+        let get_lines = |line_breaking| -> Vec<String> {
+            wrap::Wrapper::new(words.iter().map(Into::into), &context, width)
+                .with_line_breaking(line_breaking)
+                .map(|(line, _)| line.iter().map(|s| s.s.as_ref()).collect())
+                .collect()
+        };
+        // The sum of the squared space that is left on all lines except the last one
+        let get_raggedness = |lines: &[String]| -> f32 {
+            lines[..lines.len() - 1]
+                .iter()
+                .map(|line| width - Style::new().str_width(&context.font_cache, line.trim_end()))
+                .map(|shortfall| {
+                    assert!(shortfall >= Mm(0.0));
+                    let shortfall: f32 = shortfall.into();
+                    shortfall * shortfall
+                })
+                .sum()
+        };
+
+        let greedy = get_lines(LineBreaking::Greedy);
+        let optimal = get_lines(LineBreaking::Optimal);
+        assert_eq!(text, optimal.concat());
+        assert!(get_raggedness(&optimal) < get_raggedness(&greedy));
+
+        // A word that is wider than the line falls back to greedy line breaking
+        let long_word = StyledStr::new("Donaudampfschifffahrtsgesellschaft", Style::new());
+        assert!(wrap::break_optimal(&[long_word], &context, Mm(20.0)).is_none());
+    }
+
+    #[test]
+    fn test_paragraph_page_breaks() {
+        let context = get_context();
+        let line_height = Style::new().metrics(&context.font_cache).line_height;
+        // Five lines, but only four fit into the area
+        let area = render::Area::measuring(Size::new(12, line_height * 4.5));
+        // The same area with one line of content above it
+        let mut area_below = area.clone();
+        area_below.add_content_offset(line_height);
+        let get_paragraph = || Paragraph::new("one two three four five");
+        let render_lines_into = |paragraph: &mut Paragraph, area: &render::Area<'_>| {
+            let result = paragraph
+                .render(&context, area.clone(), Style::new())
+                .expect("Rendering failed");
+            let lines = f32::from(result.size.height) / f32::from(line_height);
+            lines.round() as usize
+        };
+        let render_lines = |paragraph: &mut Paragraph| render_lines_into(paragraph, &area);
+
+        assert_eq!(4, render_lines(&mut get_paragraph()));
+        assert_eq!(3, render_lines(&mut get_paragraph().with_widows(2)));
+        let mut paragraph = get_paragraph().with_widows(2).with_orphans(4);
+        assert_eq!(0, render_lines_into(&mut paragraph, &area_below));
+        // The limits are ignored at the top of a page if no line fits otherwise
+        assert_eq!(4, render_lines(&mut paragraph));
+        assert_eq!(1, render_lines(&mut paragraph));
+        let mut paragraph = get_paragraph().with_widows(5);
+        assert_eq!(4, render_lines(&mut paragraph));
+
+        let mut paragraph = Paragraph::new("one two three four").with_keep_together(true);
+        assert_eq!(0, render_lines_into(&mut paragraph, &area_below));
+        assert_eq!(4, render_lines(&mut paragraph));
+        // The paragraph is broken as usual if it does not fit on an empty page either
+        let mut paragraph = get_paragraph().with_keep_together(true);
+        assert_eq!(3, render_lines_into(&mut paragraph, &area_below));
+        assert_eq!(2, render_lines(&mut paragraph));
+        let mut paragraph = get_paragraph().with_keep_together(true);
+        assert_eq!(4, render_lines(&mut paragraph));
+    }
+
+    #[test]
+    fn test_orphans_on_empty_page() {
+        let mut doc = get_document();
+        let line_height = Style::new().line_height(doc.font_cache());
+        doc.set_paper_size(Size::new(12, line_height * 3.5));
+        doc.push(Paragraph::new("one two three four five").with_orphans(4));
+
+        // The orphan control is ignored because the paragraph would not fit on any page.
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load document");
+        assert_eq!(2, pdf.get_pages().len());
+    }
+
+    #[test]
+    fn test_keep_together() {
+        let context = get_context();
+        let line_height = Style::new().metrics(&context.font_cache).line_height;
+        let area = render::Area::measuring(Size::new(12, line_height * 4.5));
+        // The same area with one line of content above it
+        let mut area_below = area.clone();
+        area_below.add_content_offset(line_height);
+        let render_lines_into = |element: &mut dyn crate::Element, area: &render::Area<'_>| {
+            let result = element
+                .render(&context, area.clone(), Style::new())
+                .expect("Rendering failed");
+            let lines = f32::from(result.size.height) / f32::from(line_height);
+            (lines.round() as usize, result.has_more)
+        };
+        let render_lines = |element: &mut dyn crate::Element| render_lines_into(element, &area);
+
+        let mut element = KeepTogether::new(Paragraph::new("one two three four"));
+        assert_eq!((0, true), render_lines_into(&mut element, &area_below));
+        assert_eq!((4, false), render_lines(&mut element));
+
+        // The element is broken as usual if it does not fit on an empty page either
+        let mut element = KeepTogether::new(Paragraph::new("one two three four five"));
+        assert_eq!((3, true), render_lines_into(&mut element, &area_below));
+        assert_eq!((2, false), render_lines(&mut element));
+        let mut element = KeepTogether::new(Paragraph::new("one two three four five"));
+        assert_eq!((4, true), render_lines(&mut element));
+        assert_eq!((1, false), render_lines(&mut element));
+
+        let mut element = KeepTogether::new(Paragraph::new("one two three four"));
+        assert_eq!((4, false), render_lines(&mut element));
+
+        // The heading is moved because the paragraph requires two lines at the bottom of the page
+        let mut layout = LinearLayout::vertical()
+            .element(Paragraph::new("one two"))
+            .element_keep_with_next(Paragraph::new("title"))
+            .element(Paragraph::new("one two three").with_orphans(2));
+        assert_eq!((2, true), render_lines(&mut layout));
+        assert_eq!((4, false), render_lines(&mut layout));
+    }
+
+    #[test]
+    fn test_keep_together_with_padding() {
+        let mut doc = get_document();
+        let line_height = Style::new().line_height(doc.font_cache());
+        doc.set_paper_size(Size::new(30, line_height * 4.5));
+        let paragraph =
+            Paragraph::new("aaaaaaaa bbbbbbbb cccccccc dddddddd").with_keep_together(true);
+        doc.push(paragraph.padded(Margins::trbl(line_height, 0, 0, 0)));
+
+        // The paragraph does not fit into the padded area of an empty page, so it is broken on
+        // the first page instead of being moved to the next page.
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load document");
+        let pages: Vec<_> = (1..=pdf.get_pages().len() as u32)
+            .map(|page| pdf.extract_text(&[page]).unwrap().replace(['\n', ' '], ""))
+            .collect();
+        assert_eq!(vec!["aaaaaaaabbbbbbbbcccccccc", "dddddddd"], pages);
+    }
+
+    #[test]
+    fn test_columns() {
+        let context = get_context();
+        let line_height = Style::new().metrics(&context.font_cache).line_height;
+        let render_lines = |element: &mut Columns, lines: f32| {
+            let area = render::Area::measuring(Size::new(100, line_height * lines));
+            let result = element
+                .render(&context, area, Style::new())
+                .expect("Rendering failed");
+            let lines = f32::from(result.size.height) / f32::from(line_height);
+            (lines.round() as usize, result.has_more)
+        };
+        let get_columns = |balanced| {
+            let mut columns = Columns::new(2).with_gutter(10).with_balanced(balanced);
+            for _ in 0..7 {
+                columns.push(Paragraph::new("text"));
+            }
+            columns
+        };
+
+        // The content flows into the second column and is continued on the next page, where the
+        // columns are balanced.
+        let mut columns = get_columns(true);
+        assert_eq!((2, true), render_lines(&mut columns, 2.5));
+        assert_eq!((2, false), render_lines(&mut columns, 10.0));
+
+        columns.reset();
+        assert_eq!((4, false), render_lines(&mut columns, 10.0));
+
+        let mut columns = get_columns(false);
+        assert_eq!((7, false), render_lines(&mut columns, 10.0));
+
+        // A paragraph that is continued on the next page is balanced with its remaining lines.
+        let text = "text ".repeat(60);
+        let area = render::Area::measuring(Size::new(45, 1000));
+        let result = Paragraph::new(text.as_str()).measure(&context, area, Style::new());
+        let lines = f32::from(result.unwrap().size.height) / f32::from(line_height);
+        let lines = lines.round() as usize;
+        let mut columns = Columns::new(2)
+            .with_gutter(10)
+            .element(Paragraph::new(text));
+        assert_eq!((2, true), render_lines(&mut columns, 2.5));
+        assert_eq!(((lines - 3) / 2, false), render_lines(&mut columns, 100.0));
+
+        // A page break in the content starts a new column.
+        let mut columns = Columns::new(2)
+            .element(Paragraph::new("text"))
+            .element(PageBreak::new())
+            .element(Paragraph::new("text"));
+        assert_eq!((1, false), render_lines(&mut columns, 10.0));
+    }
+
+    #[test]
+    fn test_floats() {
+        let context = get_context();
+        let line_height = Style::new().metrics(&context.font_cache).line_height;
+        let eq = |left, right| approx_eq!(Mm, left, right, epsilon = 0.001);
+
+        let area = render::Area::measuring(Size::new(100, 100));
+        area.add_float((0, 10), (30, 10));
+        area.add_float((80, 0), (20, 15));
+        let (offset, width) = area.line_bounds(Mm(0.0), Mm(5.0));
+        assert!(eq(Mm(0.0), offset) && eq(Mm(80.0), width));
+        let (offset, width) = area.line_bounds(Mm(12.0), Mm(5.0));
+        assert!(eq(Mm(30.0), offset) && eq(Mm(50.0), width));
+        let (offset, width) = area.line_bounds(Mm(20.0), Mm(5.0));
+        assert!(eq(Mm(0.0), offset) && eq(Mm(100.0), width));
+
+        // The text next to the float is wrapped with the remaining width.
+        let text = "one two three four five six seven";
+        let render_lines = |float: Option<Float<Paragraph>>| {
+            let area = render::Area::measuring(Size::new(30, 100));
+            let mut layout = LinearLayout::vertical();
+            if let Some(float) = float {
+                layout.push(float);
+            }
+            layout.push(Paragraph::new(text));
+            let result = layout
+                .render(&context, area.clone(), Style::new())
+                .expect("Rendering failed");
+            let lines = f32::from(result.size.height) / f32::from(line_height);
+            (
+                lines.round() as usize,
+                area.line_bounds(Mm(0.0), line_height),
+            )
+        };
+        let (lines, (_, width)) = render_lines(None);
+        assert!(eq(Mm(30.0), width));
+        let float = Float::right(Paragraph::new("title title"))
+            .with_width(12)
+            .with_gap(2);
+        let (float_lines, (offset, width)) = render_lines(Some(float));
+        let float_width = Paragraph::new("title title")
+            .measure(
+                &context,
+                render::Area::measuring(Size::new(12, 100)),
+                Style::new(),
+            )
+            .expect("Measuring failed")
+            .size
+            .width;
+        assert!(float_lines > lines);
+        assert!(eq(Mm(0.0), offset) && eq(Mm(28.0) - float_width, width));
+    }
+
+    #[test]
+    fn test_measure() {
+        use crate::style::LineStyle;
+
+        let context = get_context();
+        let line_height = Style::new().metrics(&context.font_cache).line_height;
+        let mut table = TableLayout::new(vec![1, 1]);
+        table
+            .row()
+            .element(Paragraph::new("one two three four five"))
+            .element(Text::new("A"))
+            .push()
+            .unwrap();
+        table
+            .row()
+            .element(Text::new("B"))
+            .element(Text::new("C"))
+            .push()
+            .unwrap();
+        let mut element = table.framed(LineStyle::new());
+
+        let area = render::Area::measuring(Size::new(24, 200));
+        let size = element
+            .measure(&context, area.clone(), Style::new())
+            .unwrap()
+            .size;
+        assert!(size.height > line_height * 6.0);
+        // Measuring leaves the element in its initial rendering state
+        let measured = element
+            .measure(&context, area.clone(), Style::new())
+            .unwrap();
+        assert_eq!(size, measured.size);
+        let result = element.render(&context, area, Style::new()).unwrap();
+        assert_eq!(size, result.size);
+        assert!(!result.has_more);
+
+        // Only the content that fits into the available size is measured
+        let mut paragraph = Paragraph::new("one two three four five");
+        let area = render::Area::measuring(Size::new(12, line_height * 4.5));
+        let result = paragraph
+            .measure(&context, area.clone(), Style::new())
+            .unwrap();
+        assert_eq!(line_height * 4.0, result.size.height);
+        assert!(result.has_more);
+
+        // A partially rendered paragraph measures its remaining content without consuming it
+        paragraph
+            .render(&context, area.clone(), Style::new())
+            .unwrap();
+        let measured = paragraph
+            .measure(&context, area.clone(), Style::new())
+            .unwrap();
+        let result = paragraph.render(&context, area, Style::new()).unwrap();
+        assert_eq!(result, measured);
+        assert!(!result.has_more);
+    }
+
+    #[test]
+    fn test_measure_started_layout() {
+        let context = get_context();
+        let line_height = Style::new().metrics(&context.font_cache).line_height;
+        let mut layout = LinearLayout::vertical();
+        layout.push(Paragraph::new("one two three four five"));
+        layout.push(Paragraph::new("six"));
+        let area = render::Area::measuring(Size::new(12, line_height * 1.5));
+        layout.render(&context, area, Style::new()).unwrap();
+
+        // A vertical layout measures its remaining content without consuming it.
+        let area = render::Area::measuring(Size::new(12, 100));
+        let measured = layout
+            .measure(&context, area.clone(), Style::new())
+            .unwrap();
+        let result = layout.render(&context, area.clone(), Style::new()).unwrap();
+        assert_eq!(result, measured);
+        assert!(!result.has_more);
+
+        // A table cannot be measured after it has been rendered partially.
+        let mut table = TableLayout::new(vec![1]);
+        for text in &["one", "two"] {
+            table.row().element(Text::new(*text)).push().unwrap();
+        }
+        let area = render::Area::measuring(Size::new(100, line_height * 1.5));
+        table.render(&context, area.clone(), Style::new()).unwrap();
+        assert!(table.measure(&context, area, Style::new()).is_err());
+    }
+
+    #[test]
+    fn test_bidi_reorder() {
+        use crate::style::StyledCow;
+        use crate::wrap;
+
+        assert!(!wrap::is_rtl(vec!["123 ", "abc שלום"]));
+        assert!(wrap::is_rtl(vec!["123 ", "שלום abc"]));
+
+        let line = vec![
+            StyledCow::new("abc ", Style::new()),
+            StyledCow::new("שלום ", Style::new().bold()),
+            StyledCow::new("עולם 42", Style::new()),
+        ];
+        let text: String = line.iter().map(|s| s.s.as_ref()).collect();
+        let levels = wrap::resolve_levels(&text, false);
+        let parts: Vec<_> = wrap::reorder(&line, &levels, false)
+            .into_iter()
+            .map(|(s, is_rtl)| (s.s, is_rtl))
+            .collect();
+        assert_eq!(
+            vec![
+                ("abc ", false),
+                ("42", false),
+                ("עולם ", true),
+                ("שלום ", true),
+            ],
+            parts
+        );
+
+        let levels = wrap::resolve_levels(&text, true);
+        let parts: Vec<_> = wrap::reorder(&line, &levels, true)
+            .into_iter()
+            .map(|(s, is_rtl)| (s.s, is_rtl))
+            .collect();
+        assert_eq!(
+            vec![
+                ("42", false),
+                ("עולם ", true),
+                ("שלום ", true),
+                (" ", true),
+                ("abc", false),
+            ],
+            parts
+        );
+
+        // The levels are resolved for the whole paragraph, so the exclamation mark between the two
+        // left-to-right words stays left-to-right even if the line is broken after it.
+        let text = "שלום abc! def עולם";
+        let levels = wrap::resolve_levels(text, true);
+        let line = vec![
+            StyledCow::new("שלום ", Style::new()),
+            StyledCow::new("abc! ", Style::new()),
+        ];
+        let parts: Vec<_> = wrap::reorder(&line, &levels[..14], true)
+            .into_iter()
+            .map(|(s, is_rtl)| (s.s, is_rtl))
+            .collect();
+        assert_eq!(vec![(" ", true), ("abc!", false), ("שלום ", true)], parts);
+    }
+
+    #[test]
+    fn test_links() {
+        let mut doc = get_document();
+        doc.set_paper_size(Size::new(45, 100));
+        let issue = doc.add_link(Link::uri("https://example.com"));
+        let appendix = doc.add_link(Link::destination("appendix"));
+        assert_eq!(issue, doc.add_link(Link::uri("https://example.com")));
+        doc.push(
+            Paragraph::default()
+                .string("See ")
+                .styled_string("the ticket in our issue tracker", issue)
+                .string(" and ")
+                .styled_string("the appendix", appendix),
+        );
+        doc.push(Text::new("Issues").linked(Link::uri("https://example.com/issues")));
+        doc.push(PageBreak::new());
+        doc.push(Text::new("Appendix").anchored("appendix"));
+
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load document");
+        let mut actions = Vec::new();
+        for object in pdf.objects.values() {
+            if let Ok(dict) = object.as_dict() {
+                if dict.get(b"Subtype").and_then(lopdf::Object::as_name).ok() == Some(b"Link") {
+                    let action = dict.get(b"A").unwrap().as_dict().unwrap();
+                    let target = action
+                        .get(b"URI")
+                        .or_else(|_| action.get(b"D"))
+                        .and_then(lopdf::Object::as_str)
+                        .unwrap();
+                    actions.push(String::from_utf8_lossy(target).into_owned());
+                }
+            }
+        }
+        actions.sort();
+        // Both text links are wrapped and need one annotation per line.
+        assert_eq!(
+            vec![
+                "appendix",
+                "appendix",
+                "https://example.com",
+                "https://example.com",
+                "https://example.com/issues",
+            ],
+            actions
+        );
+
+        let names = pdf
+            .catalog()
+            .unwrap()
+            .get(b"Names")
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        let dests_id = names.get(b"Dests").unwrap().as_reference().unwrap();
+        let dests = pdf.get_dictionary(dests_id).unwrap();
+        let dests = dests.get(b"Names").unwrap().as_array().unwrap();
+        assert_eq!(b"appendix", dests[0].as_str().unwrap());
+        let page_id = dests[1].as_array().unwrap()[0].as_reference().unwrap();
+        assert_eq!(Some(&page_id), pdf.get_pages().get(&2));
+    }
+
+    #[test]
+    fn test_link_of_other_document() {
+        let mut other = get_document();
+        let link = other.add_link(Link::uri("https://example.com"));
+        let mut doc = get_document();
+        assert_eq!(link, other.add_link(Link::uri("https://example.com")));
+        assert_ne!(link, doc.add_link(Link::uri("https://example.com")));
+        doc.push(Paragraph::default().styled_string("Example", link));
+        assert!(doc.render(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_link_to_unknown_destination() {
+        let mut doc = get_document();
+        doc.push(Text::new("Appendix").linked(Link::destination("appendix")));
+        assert!(doc.render(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_outline() {
+        let mut doc = get_document();
+        doc.push(Heading::new("Introduction"));
+        doc.push(Heading::new("Motivation").with_level(2));
+        doc.push(Heading::new("Scope").with_level(2));
+        doc.push(PageBreak::new());
+        doc.push(Heading::new("Usage"));
+        doc.push(Heading::new("Details").with_level(3));
+
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load document");
+        let catalog = pdf.catalog().unwrap();
+        assert_eq!(
+            b"UseOutlines",
+            catalog.get(b"PageMode").unwrap().as_name().unwrap()
+        );
+        let root = pdf
+            .get_dictionary(catalog.get(b"Outlines").unwrap().as_reference().unwrap())
+            .unwrap();
+        assert_eq!(5, root.get(b"Count").unwrap().as_i64().unwrap());
+
+        // Returns the titles and the number of descendants of the given item and its siblings.
+        fn items(pdf: &lopdf::Document, first: &lopdf::Object) -> Vec<(String, i64)> {
+            let mut items = Vec::new();
+            let mut next = first.as_reference().ok();
+            while let Some(id) = next {
+                let item = pdf.get_dictionary(id).unwrap();
+                let title = item.get(b"Title").unwrap().as_str().unwrap();
+                let count = item.get(b"Count").and_then(|c| c.as_i64()).unwrap_or(0);
+                items.push((String::from_utf8_lossy(title).into_owned(), count));
+                next = item.get(b"Next").and_then(lopdf::Object::as_reference).ok();
+            }
+            items
+        }
+        assert_eq!(
+            vec![("Introduction".to_owned(), 2), ("Usage".to_owned(), 1)],
+            items(&pdf, root.get(b"First").unwrap())
+        );
+        let first = pdf
+            .get_dictionary(root.get(b"First").unwrap().as_reference().unwrap())
+            .unwrap();
+        assert_eq!(
+            vec![("Motivation".to_owned(), 0), ("Scope".to_owned(), 0)],
+            items(&pdf, first.get(b"First").unwrap())
+        );
+        let last = pdf
+            .get_dictionary(root.get(b"Last").unwrap().as_reference().unwrap())
+            .unwrap();
+        let dest = last.get(b"Dest").unwrap().as_array().unwrap();
+        assert_eq!(
+            Some(&dest[0].as_reference().unwrap()),
+            pdf.get_pages().get(&2)
+        );
+    }
+
+    #[test]
+    fn test_table_of_contents() {
+        let mut doc = get_document();
+        let line_height = Style::new().line_height(doc.font_cache());
+        // The table of contents needs two pages, which shifts the following pages.
+        doc.set_paper_size(Size::new(80, line_height * 4.5));
+        doc.push(TableOfContents::new().with_max_level(1));
+        for i in 1..=6 {
+            doc.push(PageBreak::new());
+            doc.push(Heading::new(format!("Chapter{}", i)));
+            doc.push(Heading::new(format!("Section{}", i)).with_level(2));
+        }
+
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load document");
+        assert_eq!(8, pdf.get_pages().len());
+        let pages: Vec<_> = (1..=2)
+            .map(|page| {
+                pdf.extract_text(&[page])
+                    .unwrap()
+                    .replace(['\n', ' ', '.'], "")
+            })
+            .collect();
+        assert_eq!(
+            vec!["Chapter13Chapter24Chapter35Chapter46", "Chapter57Chapter68"],
+            pages
+        );
+    }
+
+    #[test]
+    fn test_table_of_contents_long_heading() {
+        let mut doc = get_document();
+        let line_height = Style::new().line_height(doc.font_cache());
+        doc.set_paper_size(Size::new(60, line_height * 4.5));
+        doc.push(TableOfContents::new());
+        doc.push(PageBreak::new());
+        doc.push(Heading::new(
+            "A chapter with a title that does not fit on one line",
+        ));
+        doc.push(Heading::new("Short"));
+
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load document");
+        let text = pdf.extract_text(&[1]).unwrap();
+        // The title is wrapped and the page number is printed after the leaders on its last line.
+        let lines: Vec<_> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.chars().all(|c| c == '.'))
+            .collect();
+        assert_eq!(
+            vec![
+                "A chapter with a title that",
+                "does not fit on one line",
+                "2",
+                "Short",
+                "2"
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn test_unstable_headings() {
+        // Registers one heading more than in the previous rendering process.
+        struct GrowingHeadings;
+
+        impl crate::Element for GrowingHeadings {
+            fn render(
+                &mut self,
+                context: &Context,
+                _area: render::Area<'_>,
+                _style: Style,
+            ) -> Result<crate::RenderResult, crate::error::Error> {
+                for _ in 0..=context.headings().len() {
+                    context.register_heading("Heading", 1);
+                }
+                Ok(Default::default())
+            }
+        }
+
+        let mut doc = get_document();
+        doc.push(GrowingHeadings);
+        let err = doc.render(&mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::UnstableLayout
+        ));
+    }
 }
@@ -204,9 +204,9 @@ impl Image {
     /// Determines the offset from left-side based on provided Alignment.
     fn get_offset(&self, width: Mm, max_width: Mm) -> Position {
         let horizontal_offset = match self.alignment {
-            Alignment::Left => Mm::default(),
+            Alignment::Left | Alignment::Start | Alignment::Justified => Mm::default(),
             Alignment::Center => (max_width - width) / 2.0,
-            Alignment::Right => max_width - width,
+            Alignment::Right | Alignment::End => max_width - width,
         };
         Position::new(horizontal_offset, 0)
     }
@@ -78,6 +78,7 @@ impl error::Error for Error {
             ErrorKind::InvalidFont => None,
             ErrorKind::PageSizeExceeded => None,
             ErrorKind::UnsupportedEncoding => None,
+            ErrorKind::UnstableLayout => None,
             ErrorKind::IoError(err) => Some(err),
             ErrorKind::PdfError(err) => Some(err),
             ErrorKind::PdfIndexError(err) => Some(err),
@@ -102,6 +103,8 @@ pub enum ErrorKind {
     PageSizeExceeded,
     /// A string with unsupported characters was used with a built-in font.
     UnsupportedEncoding,
+    /// The headings of the document still changed after the maximum number of rendering passes.
+    UnstableLayout,
     /// An IO error.
     IoError(io::Error),
     /// An error caused by invalid data in `printpdf`.
@@ -26,6 +26,16 @@
 //! Built-in fonts can only be used with characters that are supported by the [Windows-1252][]
 //! encoding.
 //!
+//! If your text contains characters that are not supported by the selected font, for example
+//! characters from other scripts, you can add fallback font families to the font cache using
+//! [`Document::add_fallback_font_family`][].  Every character that is not supported by the font of
+//! the current style is then printed with the first fallback font that has a glyph for it.
+//!
+//! Per default, every character is mapped to one glyph of the font, and only pair kerning is
+//! applied.  If the `shaping` feature is enabled, text printed with an embedded font is shaped
+//! using the [`rustybuzz`][] crate instead (see [`Font::shape`][]), so ligatures, contextual forms
+//! and mark positioning are applied as required for scripts like Arabic, Hebrew or Devanagari.
+//!
 //! **Note:**  The [`Font`][] and [`FontFamily<Font>`][`FontFamily`] structs are only valid for the
 //! [`FontCache`][] they have been created with.  If you dont use the low-level [`render`][] module
 //! directly, only use the [`Document::add_font_family`][] method to add fonts!
@@ -36,7 +46,9 @@
 //! metrics that is used to calculate the text size.  It can be loaded at any time using the
 //! [`FontData::load`][] and [`FontData::new`][] methods.  Once the PDF document is rendered, a
 //! [`printpdf::IndirectFontRef`][] is used to draw text in the PDF document.  Before a font can be
-//! used in a PDF document, it has to be embedded using the [`FontCache::load_pdf_fonts`][] method.
+//! used in a PDF document, the font cache has to be prepared using the
+//! [`FontCache::load_pdf_fonts`][] method.  The fonts are then embedded into the document when
+//! they are first used.
 //!
 //! If you use the high-level interface provided by [`Document`][] to generate a PDF document, these
 //! steps are done automatically.  You only have to manually populate the font cache if you use the
@@ -45,6 +57,7 @@
 //! [`render`]: ../render/
 //! [`Document`]: ../struct.Document.html
 //! [`Document::add_font_family`]: ../struct.Document.html#method.add_font_family
+//! [`Document::add_fallback_font_family`]: ../struct.Document.html#method.add_fallback_font_family
 //! [`Style`]: ../style/struct.Style.html
 //! [`from_files`]: fn.from_files.html
 //! [`Builtin`]: enum.Builtin.html
@@ -55,15 +68,20 @@
 //! [`FontData::load`]: struct.FontData.html#method.load
 //! [`Font`]: struct.Font.html
 //! [`FontFamily`]: struct.FontFamily.html
+//! [`Font::shape`]: struct.Font.html#method.shape
 //! [`rusttype`]: https://docs.rs/rusttype
+//! [`rustybuzz`]: https://docs.rs/rustybuzz
 //! [`rusttype::Font`]: https://docs.rs/rusttype/0.8.3/rusttype/struct.Font.html
 //! [`printpdf`]: https://docs.rs/printpdf
 //! [`printpdf::IndirectFontRef`]: https://docs.rs/printpdf/0.3.2/printpdf/types/plugins/graphics/two_dimensional/font/struct.IndirectFontRef.html
 //! [Windows-1252]: https://en.wikipedia.org/wiki/Windows-1252
 
+use std::cell;
 use std::fmt;
 use std::fs;
 use std::path;
+use std::rc;
+use std::sync;
 
 use crate::error::{Context as _, Error, ErrorKind};
 use crate::render;
@@ -81,11 +99,14 @@ use crate::Mm;
 #[derive(Debug)]
 pub struct FontCache {
     fonts: Vec<FontData>,
-    pdf_fonts: Vec<printpdf::IndirectFontRef>,
+    // The PDF fonts are embedded when they are first used, see get_pdf_font.
+    pdf_fonts: cell::RefCell<Vec<Option<printpdf::IndirectFontRef>>>,
+    pdf_doc: rc::Weak<printpdf::PdfDocumentReference>,
     // We have to use an option because we first have to construct the FontCache before we can load
     // a font, but the default font is always loaded in new, so this options is always some
     // (outside of new).
     default_font_family: Option<FontFamily<Font>>,
+    fallback_font_families: Vec<FontFamily<Font>>,
 }
 
 impl FontCache {
@@ -93,8 +114,10 @@ impl FontCache {
     pub fn new(default_font_family: FontFamily<FontData>) -> FontCache {
         let mut font_cache = FontCache {
             fonts: Vec::new(),
-            pdf_fonts: Vec::new(),
+            pdf_fonts: cell::RefCell::new(Vec::new()),
+            pdf_doc: rc::Weak::new(),
             default_font_family: None,
+            fallback_font_families: Vec::new(),
         };
         font_cache.default_font_family = Some(font_cache.add_font_family(default_font_family));
         font_cache
@@ -121,17 +144,35 @@ impl FontCache {
         }
     }
 
-    /// Embeds all loaded fonts into the document generated by the given renderer and caches a
-    /// reference to them.
+    /// Adds the given font family to the cache as a fallback font family and returns a reference
+    /// to it.
+    ///
+    /// If the font selected by a [`Style`][] does not contain a glyph for a character, the
+    /// character is printed with the first fallback font family that has a glyph for it instead.
+    /// The fallback font families are tried in the order they have been added.
+    ///
+    /// [`Style`]: ../style/struct.Style.html
+    pub fn add_fallback_font_family(&mut self, family: FontFamily<FontData>) -> FontFamily<Font> {
+        let family = self.add_font_family(family);
+        self.fallback_font_families.push(family);
+        family
+    }
+
+    /// Returns the fallback font families for this font cache in the order they are tried.
+    pub fn fallback_font_families(&self) -> &[FontFamily<Font>] {
+        &self.fallback_font_families
+    }
+
+    /// Prepares the loaded fonts for the document generated by the given renderer.
+    ///
+    /// The fonts are not embedded into the document immediately.  Instead, a font is embedded when
+    /// it is first used, see [`get_pdf_font`][].  Fonts that are never used do not increase the
+    /// size of the generated document.
+    ///
+    /// [`get_pdf_font`]: #method.get_pdf_font
     pub fn load_pdf_fonts(&mut self, renderer: &render::Renderer) -> Result<(), Error> {
-        self.pdf_fonts.clear();
-        for font in &self.fonts {
-            let pdf_font = match &font.raw_data {
-                RawFontData::Builtin(builtin) => renderer.add_builtin_font(*builtin)?,
-                RawFontData::Embedded(data) => renderer.add_embedded_font(data)?,
-            };
-            self.pdf_fonts.push(pdf_font);
-        }
+        self.pdf_fonts = cell::RefCell::new(vec![None; self.fonts.len()]);
+        self.pdf_doc = renderer.downgrade_doc();
         Ok(())
     }
 
@@ -141,15 +182,33 @@ impl FontCache {
             .expect("Invariant violated: no default font family for FontCache")
     }
 
-    /// Returns a reference to the emebdded PDF font for the given font, if available.
+    /// Returns a reference to the embedded PDF font for the given font, if available.
     ///
-    /// This method may only be called with [`Font`][] instances that have been created by this
-    /// font cache.  PDF fonts are only avaiable if [`load_pdf_fonts`][] has been called.
+    /// If the font has not been used yet, it is added to the document generated by the renderer
+    /// passed to [`load_pdf_fonts`][].  This method may only be called with [`Font`][] instances
+    /// that have been created by this font cache.  PDF fonts are only avaiable if
+    /// [`load_pdf_fonts`][] has been called and the renderer has not been dropped yet.
     ///
     /// [`Font`]: struct.Font.html
     /// [`load_pdf_fonts`]: #method.load_pdf_fonts
-    pub fn get_pdf_font(&self, font: Font) -> Option<&printpdf::IndirectFontRef> {
-        self.pdf_fonts.get(font.idx)
+    pub fn get_pdf_font(&self, font: Font) -> Result<Option<printpdf::IndirectFontRef>, Error> {
+        let mut pdf_fonts = self.pdf_fonts.borrow_mut();
+        let pdf_font = match pdf_fonts.get_mut(font.idx) {
+            Some(pdf_font) => pdf_font,
+            None => return Ok(None),
+        };
+        if pdf_font.is_none() {
+            let doc = match self.pdf_doc.upgrade() {
+                Some(doc) => doc,
+                None => return Ok(None),
+            };
+            let result = match &self.fonts[font.idx].raw_data {
+                RawFontData::Builtin(builtin) => doc.add_builtin_font(*builtin),
+                RawFontData::Embedded(data) => doc.add_external_font(&data[..]),
+            };
+            *pdf_font = Some(result.context("Failed to load PDF font")?);
+        }
+        Ok(pdf_font.clone())
     }
 
     /// Returns a reference to the Rusttype font for the given font, if available.
@@ -170,6 +229,8 @@ impl FontCache {
 pub struct FontData {
     rt_font: rusttype::Font<'static>,
     raw_data: RawFontData,
+    #[cfg(feature = "shaping")]
+    shaping_face: Option<ShapingFace>,
 }
 
 impl FontData {
@@ -185,7 +246,12 @@ impl FontData {
         let raw_data = if let Some(builtin) = builtin {
             RawFontData::Builtin(builtin)
         } else {
-            RawFontData::Embedded(data.clone())
+            RawFontData::Embedded(data.as_slice().into())
+        };
+        #[cfg(feature = "shaping")]
+        let shaping_face = match &raw_data {
+            RawFontData::Builtin(_) => None,
+            RawFontData::Embedded(data) => ShapingFace::new(data.clone()),
         };
         let rt_font = rusttype::Font::try_from_vec(data)
             .ok_or_else(|| Error::new("Failed to read rusttype font", ErrorKind::InvalidFont))?;
@@ -195,7 +261,12 @@ impl FontData {
                 ErrorKind::InvalidFont,
             ))
         } else {
-            Ok(FontData { rt_font, raw_data })
+            Ok(FontData {
+                rt_font,
+                raw_data,
+                #[cfg(feature = "shaping")]
+                shaping_face,
+            })
         }
     }
 
@@ -220,7 +291,69 @@ impl FontData {
 #[derive(Clone, Debug)]
 enum RawFontData {
     Builtin(printpdf::BuiltinFont),
-    Embedded(Vec<u8>),
+    Embedded(sync::Arc<[u8]>),
+}
+
+/// The data of a font that is used for shaping with `rustybuzz`.  The face is parsed from the
+/// shared font data of the `RawFontData` when a string is shaped, and the shape plans are created
+/// once per direction and script.
+#[cfg(feature = "shaping")]
+struct ShapingFace {
+    data: sync::Arc<[u8]>,
+    units_per_em: i32,
+    plans: cell::RefCell<Vec<(ShapePlanKey, rustybuzz::ShapePlan)>>,
+}
+
+#[cfg(feature = "shaping")]
+type ShapePlanKey = (rustybuzz::Direction, Option<rustybuzz::Script>);
+
+#[cfg(feature = "shaping")]
+impl ShapingFace {
+    fn new(data: sync::Arc<[u8]>) -> Option<ShapingFace> {
+        let units_per_em = rustybuzz::Face::from_slice(&data, 0)?.units_per_em();
+        Some(ShapingFace {
+            data,
+            units_per_em,
+            plans: Default::default(),
+        })
+    }
+
+    /// Shapes the given buffer, re-using the shape plan for its direction and script.
+    fn shape(&self, buffer: rustybuzz::UnicodeBuffer) -> Option<rustybuzz::GlyphBuffer> {
+        let face = rustybuzz::Face::from_slice(&self.data, 0)?;
+        let script = Some(buffer.script()).filter(|script| *script != rustybuzz::script::UNKNOWN);
+        let key = (buffer.direction(), script);
+        let mut plans = self.plans.borrow_mut();
+        let idx = match plans.iter().position(|(k, _)| *k == key) {
+            Some(idx) => idx,
+            None => {
+                let plan = rustybuzz::ShapePlan::new(&face, key.0, key.1, None, &[]);
+                plans.push((key, plan));
+                plans.len() - 1
+            }
+        };
+        Some(rustybuzz::shape_with_plan(&face, &plans[idx].1, buffer))
+    }
+}
+
+#[cfg(feature = "shaping")]
+impl Clone for ShapingFace {
+    fn clone(&self) -> ShapingFace {
+        ShapingFace {
+            data: self.data.clone(),
+            units_per_em: self.units_per_em,
+            plans: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "shaping")]
+impl fmt::Debug for ShapingFace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShapingFace")
+            .field("data", &format_args!("[{} bytes]", self.data.len()))
+            .finish()
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -362,6 +495,26 @@ impl Font {
         self.is_builtin
     }
 
+    /// Returns whether this font can print the given character.
+    ///
+    /// For embedded fonts, this checks whether the font contains a glyph for the character.
+    /// Built-in fonts can only print characters that are supported by the [Windows-1252][]
+    /// encoding.
+    ///
+    /// The given [`FontCache`][] must be the font cache that loaded this font.
+    ///
+    /// [`FontCache`]: struct.FontCache.html
+    /// [Windows-1252]: https://en.wikipedia.org/wiki/Windows-1252
+    pub fn has_glyph(&self, font_cache: &FontCache, c: char) -> bool {
+        if self.is_builtin {
+            let mut buf = [0; 4];
+            lopdf::Document::encode_text(Some("WinAnsiEncoding"), c.encode_utf8(&mut buf)).len()
+                == 1
+        } else {
+            font_cache.get_rt_font(*self).glyph(c).id().0 != 0
+        }
+    }
+
     /// Returns the line height for text with this font and the given font size.
     pub fn get_line_height(&self, font_size: u8) -> Mm {
         self.line_height * f32::from(font_size)
@@ -412,6 +565,14 @@ impl Font {
     ///
     /// [`FontCache`]: struct.FontCache.html
     pub fn str_width(&self, font_cache: &FontCache, s: &str, font_size: u8) -> Mm {
+        #[cfg(feature = "shaping")]
+        {
+            if let Some(glyphs) = self.shape(font_cache, s, crate::Direction::Auto) {
+                let width: f32 = glyphs.iter().map(|glyph| glyph.x_advance).sum();
+                return Mm::from(printpdf::Pt(width * f32::from(font_size)));
+            }
+        }
+
         let str_width: Mm = font_cache
             .get_rt_font(*self)
             .glyphs_for(s.chars())
@@ -468,6 +629,81 @@ impl Font {
             .collect()
     }
 
+    /// Shapes the given string with this font and the given direction and returns the positioned
+    /// glyphs.
+    ///
+    /// Shaping applies the ligatures, contextual forms, kerning and mark positioning defined by
+    /// the font.  The string must be in logical order.  The glyphs are returned in visual order, so
+    /// right-to-left text is reversed.  If the direction is [`Direction::Auto`][], it is guessed
+    /// from the string.  Built-in fonts cannot be shaped, so this method returns `None` for them.
+    ///
+    /// The given [`FontCache`][] must be the font cache that loaded this font.
+    ///
+    /// *Only available if the `shaping` feature is enabled.*
+    ///
+    /// [`FontCache`]: struct.FontCache.html
+    /// [`Direction::Auto`]: ../enum.Direction.html#variant.Auto
+    #[cfg(feature = "shaping")]
+    pub fn shape(
+        &self,
+        font_cache: &FontCache,
+        s: &str,
+        direction: crate::Direction,
+    ) -> Option<Vec<ShapedGlyph>> {
+        let face = font_cache.fonts[self.idx].shaping_face.as_ref()?;
+        let units_per_em = face.units_per_em as f32;
+
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(s);
+        buffer.guess_segment_properties();
+        match direction {
+            crate::Direction::Auto => {}
+            crate::Direction::LeftToRight => {
+                buffer.set_direction(rustybuzz::Direction::LeftToRight)
+            }
+            crate::Direction::RightToLeft => {
+                buffer.set_direction(rustybuzz::Direction::RightToLeft)
+            }
+        }
+        let output = face.shape(buffer)?;
+
+        let glyphs = output
+            .glyph_infos()
+            .iter()
+            .zip(output.glyph_positions())
+            .map(|(info, position)| ShapedGlyph {
+                glyph_id: info.glyph_id as u16,
+                cluster: info.cluster as usize,
+                x_advance: position.x_advance as f32 / units_per_em,
+                x_offset: position.x_offset as f32 / units_per_em,
+                y_offset: position.y_offset as f32 / units_per_em,
+            })
+            .collect();
+        Some(glyphs)
+    }
+
+    /// Returns the advance width of the glyph with the given ID in em as it is stored in the PDF
+    /// document.
+    ///
+    /// The given [`FontCache`][] must be the font cache that loaded this font.
+    ///
+    /// [`FontCache`]: struct.FontCache.html
+    #[cfg(feature = "shaping")]
+    pub(crate) fn pdf_glyph_width(&self, font_cache: &FontCache, glyph_id: u16) -> f32 {
+        let font = font_cache.get_rt_font(*self);
+        let units_per_em = f32::from(font.units_per_em());
+        let advance_width = font
+            .glyph(rusttype::GlyphId(glyph_id))
+            .scaled(self.scale)
+            .h_metrics()
+            .advance_width;
+        // printpdf stores the widths as integers in thousandths of an em, calculated from the
+        // advance width in font units
+        let units = (advance_width * units_per_em).round();
+        let width = (units * (1000.0 / units_per_em)) as i64;
+        width as f32 / 1000.0
+    }
+
     /// Calculate the metrics of a given font size for this font.
     pub fn metrics(&self, font_size: u8) -> Metrics {
         Metrics::new(
@@ -478,6 +714,28 @@ impl Font {
     }
 }
 
+/// A glyph that has been positioned by the text shaper, see [`Font::shape`][].
+///
+/// All lengths are measured in em, so they have to be multiplied with the font size.
+///
+/// *Only available if the `shaping` feature is enabled.*
+///
+/// [`Font::shape`]: struct.Font.html#method.shape
+#[cfg(feature = "shaping")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapedGlyph {
+    /// The ID of the glyph in the font.
+    pub glyph_id: u16,
+    /// The byte index of the first character of the cluster this glyph belongs to.
+    pub cluster: usize,
+    /// The horizontal distance to the next glyph.
+    pub x_advance: f32,
+    /// The horizontal offset of this glyph from the current position.
+    pub x_offset: f32,
+    /// The vertical offset of this glyph from the baseline.
+    pub y_offset: f32,
+}
+
 fn from_file(
     dir: impl AsRef<path::Path>,
     name: &str,
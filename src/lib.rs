@@ -64,8 +64,8 @@
 //!
 //! The rendering process is started by calling the [`Document::render`][] or
 //! [`Document::render_to_file`][] methods.  You can only render a document once.  Before the
-//! rendering starts, the PDF document is created and all loaded fonts are embedded into the
-//! document.
+//! rendering starts, the PDF document is created.  The loaded fonts are embedded into the document
+//! when they are first used, so fonts that are not used do not increase the document size.
 //!
 //! The elements are then rendered by calling the [`Element::render`][] method of the root element,
 //! a [`LinearLayout`][].  This element will then call the `render` methods of the elements stored
@@ -103,6 +103,18 @@
 //! there is only one rendering process per document, elements may discard data that has been
 //! rendered and that is no longer needed.
 //!
+//! If two-pass rendering is activated with [`Document::set_two_pass_rendering`][], the document is
+//! first laid out on measuring areas (see [`Area::measuring`][]) to count the pages, so that the
+//! total page count can be passed to the [`PageDecorator`][] in the [`PageInfo`][].  Afterwards,
+//! all elements are reset using [`Element::reset`][] and the document is rendered again.
+//!
+//! Rendered [`Heading`][]s are registered in the [`Context`][] together with their page number.
+//! Elements like the [`TableOfContents`][] can access the headings of the previous rendering
+//! process with [`Context::headings`][].  If they do and the headings have changed, for example
+//! because the table of contents shifted the following pages, all elements are reset and the
+//! document is rendered again until the headings are stable.  If the headings are still changing
+//! after five rendering passes, [`Document::render`][] fails with [`ErrorKind::UnstableLayout`][].
+//!
 //! # Low-Level Interface
 //!
 //! The [`render`][] module contains a low-level interface for creating PDF files.  It keeps track
@@ -114,12 +126,11 @@
 //!
 //! # Known Issues
 //!
-//! - Currently, `rckive_genpdf` adds all loaded fonts to the PDF document, even if they are not used.
-//!   `printpdf` then adds all available glyphs for these fonts to the document, even if they are
-//!   not used in the document.  This increases the file size by 100–200 KiB per font (500–1000 KiB
-//!   per font family).  Until this is fixed, you can pass the generated file through `ps2pdf` to
-//!   significantly reduce its size.  Alternatively, you can use a built-in font if you don’t need
-//!   any characters that are not supported by the [Windows-1252][] encoding.
+//! - Per default, `printpdf` adds all available glyphs of the used fonts to the document, even if
+//!   they are not used in the document.  This increases the file size by 100–200 KiB per used font.
+//!   If you activate the `font_subsetting` feature, only the glyphs that are actually used are
+//!   embedded.  Alternatively, you can use a built-in font if you don’t need any characters that
+//!   are not supported by the [Windows-1252][] encoding.
 //!
 //! [`printpdf`]: https://docs.rs/printpdf
 //! [`rusttype`]: https://docs.rs/rusttype
@@ -133,7 +144,15 @@
 //! [`Element`]: trait.Element.html
 //! [`Element::render`]: trait.Element.html#tymethod.render
 //! [`Element::styled`]: trait.Element.html#tymethod.styled
+//! [`Document::set_two_pass_rendering`]: struct.Document.html#method.set_two_pass_rendering
+//! [`Element::reset`]: trait.Element.html#method.reset
 //! [`PageDecorator`]: trait.PageDecorator.html
+//! [`PageInfo`]: struct.PageInfo.html
+//! [`Area::measuring`]: render/struct.Area.html#method.measuring
+//! [`Heading`]: elements/struct.Heading.html
+//! [`TableOfContents`]: elements/struct.TableOfContents.html
+//! [`Context::headings`]: struct.Context.html#method.headings
+//! [`ErrorKind::UnstableLayout`]: error/enum.ErrorKind.html#variant.UnstableLayout
 //! [`RenderResult`]: struct.RenderResult.html
 //! [`LinearLayout`]: elements/struct.LinearLayout.html
 //! [`StyledElement`]: elements/StyledElement.html
@@ -158,6 +177,8 @@ pub mod fonts;
 pub mod render;
 pub mod style;
 
+use std::any;
+use std::cell;
 use std::fs;
 use std::io;
 use std::path;
@@ -204,6 +225,11 @@ impl Mm {
     pub fn max(self, other: Mm) -> Mm {
         Mm(self.0.max(other.0))
     }
+
+    /// Returns the minimum of this value and the given value.
+    pub fn min(self, other: Mm) -> Mm {
+        Mm(self.0.min(other.0))
+    }
 }
 
 impl From<i8> for Mm {
@@ -269,10 +295,17 @@ impl From<Mm> for printpdf::Pt {
 
 /// The alignment of a [`Paragraph`][] or ['Image'][].
 ///
-/// The default alignment is left-flushed.
+/// The default alignment is left-flushed.  The [`Start`][] and [`End`][] alignments depend on the
+/// [`Direction`][] of a [`Paragraph`][].  For all other elements, they are equivalent to [`Left`][]
+/// and [`Right`][].
 ///
 /// [`Paragraph`]: elements/struct.Paragraph.html
 /// [`Image`]: elements/struct.Image.html
+/// [`Direction`]: enum.Direction.html
+/// [`Start`]: #variant.Start
+/// [`End`]: #variant.End
+/// [`Left`]: #variant.Left
+/// [`Right`]: #variant.Right
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
 pub enum Alignment {
     /// Left-flushed.
@@ -282,6 +315,73 @@ pub enum Alignment {
     Right,
     /// Centered.
     Center,
+    /// Flushed to the start of the line, i. e. left-flushed for left-to-right text and
+    /// right-flushed for right-to-left text.
+    Start,
+    /// Flushed to the end of the line, i. e. right-flushed for left-to-right text and left-flushed
+    /// for right-to-left text.
+    End,
+    /// Justified, i. e. the space between the words is stretched so that the lines fill the
+    /// available width.  The last line of a paragraph is flushed to the start of the line unless
+    /// it is explicitly justified, see [`Paragraph::set_justify_last_line`][].  Elements that are
+    /// not text are left-flushed.
+    ///
+    /// [`Paragraph::set_justify_last_line`]: elements/struct.Paragraph.html#method.set_justify_last_line
+    Justified,
+}
+
+/// The line breaking algorithm for a [`Paragraph`][].
+///
+/// [`Paragraph`]: elements/struct.Paragraph.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum LineBreaking {
+    /// Fill every line with as many words as possible before starting the next line.
+    #[default]
+    Greedy,
+    /// Select the line breaks that minimize the unevenness of the lines and the number of
+    /// hyphenated lines over the whole paragraph, see [`wrap::break_optimal`][].  This is
+    /// especially useful for justified text.
+    ///
+    /// [`wrap::break_optimal`]: wrap/fn.break_optimal.html
+    Optimal,
+}
+
+/// The base direction of the text in a [`Paragraph`][].
+///
+/// The base direction determines the order of the text runs with different directions according
+/// to the Unicode Bidirectional Algorithm, and the alignment for [`Alignment::Start`][] and
+/// [`Alignment::End`][].  Per default, the direction is determined from the first character with
+/// a strong direction in the paragraph.
+///
+/// [`Paragraph`]: elements/struct.Paragraph.html
+/// [`Alignment::Start`]: enum.Alignment.html#variant.Start
+/// [`Alignment::End`]: enum.Alignment.html#variant.End
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum Direction {
+    /// Determine the direction from the first character with a strong direction, or use
+    /// left-to-right if there is no such character.
+    #[default]
+    Auto,
+    /// Left-to-right text.
+    LeftToRight,
+    /// Right-to-left text.
+    RightToLeft,
+}
+
+/// The vertical alignment of the elements of a horizontal [`LinearLayout`][].
+///
+/// The default alignment is top-flushed.
+///
+/// [`LinearLayout`]: elements/struct.LinearLayout.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum VerticalAlignment {
+    /// Top-flushed.
+    #[default]
+    Top,
+    /// Centered.
+    Center,
+    /// Bottom-flushed.
+    Bottom,
 }
 
 /// A position on a PDF layer, measured in millimeters.
@@ -416,6 +516,18 @@ impl Size {
         self.height += other.height;
         self
     }
+
+    /// Returns this size in landscape orientation, i. e. with the longer side as the width.
+    #[must_use]
+    pub fn landscape(self) -> Size {
+        Size::new(self.width.max(self.height), self.width.min(self.height))
+    }
+
+    /// Returns this size in portrait orientation, i. e. with the longer side as the height.
+    #[must_use]
+    pub fn portrait(self) -> Size {
+        Size::new(self.width.min(self.height), self.width.max(self.height))
+    }
 }
 
 impl<W: Into<Mm>, H: Into<Mm>> From<(W, H)> for Size {
@@ -501,6 +613,12 @@ impl Margins {
     pub fn left(&self) -> Mm {
         self.left
     }
+
+    /// Returns these margins with the left and the right margin swapped, for example for the even
+    /// pages of a document that is printed on both sides.
+    pub fn mirrored(&self) -> Margins {
+        Margins::trbl(self.top, self.left, self.bottom, self.right)
+    }
     
 }
 
@@ -573,17 +691,11 @@ pub struct Document {
     modification_date: Option<printpdf::OffsetDateTime>,
     // add LinearLayout, multipurpose, for loading from json
     extra_layout: elements::LinearLayout,
-    // add page frame and line style
-    page_frame: bool,
-    page_frame_line_style: style::LineStyle,
-    header_frame: bool,
-    header_frame_line_style: style::LineStyle,
-    footer_frame: bool,
-    footer_frame_line_style: style::LineStyle,
-    //an approximate rec
-    rec_footer: (Position, Position),
-    // add page frame width offset
-    page_frame_width_offset: Mm,
+    overlays: Vec<(PageSelector, PositionedElement)>,
+    links: style::LinkRegistry,
+    frames: PageFrames,
+    user_data: Option<Box<dyn any::Any>>,
+    two_pass_rendering: bool,
 }
 
 impl Document {
@@ -601,43 +713,52 @@ impl Document {
             creation_date: None,
             modification_date: None,
             extra_layout: elements::LinearLayout::vertical(),
-            page_frame: false,
-            page_frame_line_style: style::LineStyle::new(),
-            header_frame: false,
-            header_frame_line_style: style::LineStyle::new(),
-            footer_frame: false,
-            footer_frame_line_style: style::LineStyle::new(),
-            rec_footer: (Position::new(0.0,0.0), Position::new(0.0,0.0)),
-            page_frame_width_offset: Mm::from(0.0),
+            overlays: Vec::new(),
+            links: style::LinkRegistry::new(),
+            frames: PageFrames::default(),
+            user_data: None,
+            two_pass_rendering: false,
         }
     }
     
     /// Set frame width offset
     pub fn set_page_frame_width_offset(&mut self, offset: impl Into<Mm>) {
-        self.page_frame_width_offset = offset.into();
+        self.frames.width_offset = offset.into();
     }
     
-    /// Set rec footer
-    pub fn set_rec_footer(&mut self, pos_ini: Position, pos_end: Position){
-        self.rec_footer = (pos_ini, pos_end);
-    }  
-    
     /// Set page frame and style
     pub fn set_page_frame_line_style(&mut self, line_style: style::LineStyle){
-        self.page_frame = true;
-        self.page_frame_line_style = line_style;
+        self.frames.page = Some(line_style);
     }     
     
     /// Set header frame and style
     pub fn set_header_frame_line_style(&mut self, line_style: style::LineStyle){
-        self.header_frame = true;
-        self.header_frame_line_style = line_style;
+        self.frames.header = Some(line_style);
     }
     
     /// Set footer frame and style
     pub fn set_footer_frame_line_style(&mut self, line_style: style::LineStyle){
-        self.footer_frame = true;
-        self.footer_frame_line_style = line_style;
+        self.frames.footer = Some(line_style);
+    }
+
+    /// Sets the frames that are drawn by the page decorator.
+    ///
+    /// See [`PageFrames`][] for the available settings.
+    ///
+    /// [`PageFrames`]: struct.PageFrames.html
+    pub fn set_page_frames(&mut self, frames: PageFrames) {
+        self.frames = frames;
+    }
+
+    /// Sets custom data that is passed to the page decorator.
+    ///
+    /// The data can be accessed with [`PageContext::user_data`][] in custom implementations of the
+    /// [`PageDecorator`][] trait.
+    ///
+    /// [`PageContext::user_data`]: struct.PageContext.html#method.user_data
+    /// [`PageDecorator`]: trait.PageDecorator.html
+    pub fn set_user_data<T: any::Any>(&mut self, data: T) {
+        self.user_data = Some(Box::new(data));
     }
     
     /// Skip the page size exceeded warning
@@ -658,6 +779,33 @@ impl Document {
         self.context.font_cache.add_font_family(font_family)
     }
 
+    /// Adds the given font family to the font cache for this document as a fallback font family
+    /// and returns a reference to it.
+    ///
+    /// Characters that are not supported by the font of the current style are printed with the
+    /// first fallback font family that has a glyph for them.  See
+    /// [`FontCache::add_fallback_font_family`][] for details.
+    ///
+    /// [`FontCache::add_fallback_font_family`]: fonts/struct.FontCache.html#method.add_fallback_font_family
+    pub fn add_fallback_font_family(
+        &mut self,
+        font_family: fonts::FontFamily<fonts::FontData>,
+    ) -> fonts::FontFamily<fonts::Font> {
+        self.context.font_cache.add_fallback_font_family(font_family)
+    }
+
+    /// Registers the given link target for this document and returns a reference to it.
+    ///
+    /// Text that is printed with the returned reference in its [`Style`][] is turned into a
+    /// clickable link.  If the same target is registered several times, the same reference is
+    /// returned.  The returned reference may only be used for this document, otherwise rendering
+    /// fails.
+    ///
+    /// [`Style`]: style/struct.Style.html
+    pub fn add_link(&mut self, link: style::Link) -> style::LinkId {
+        self.links.add(link)
+    }
+
     /// Returns the font cache used by this document.
     ///
     /// You can use the font cache to get the default font and to query glyph metrics for a font.
@@ -704,9 +852,13 @@ impl Document {
 
     /// Sets the paper size for all pages of this document.
     ///
-    /// If this method is not called, the default size [`A4`][] is used.
+    /// If this method is not called, the default size [`A4`][] is used.  The paper size of the
+    /// following pages can be changed with a [`PageBreak`][] with a size, see
+    /// [`PageBreak::with_size`][].
     ///
     /// [`A4`]: enum.PaperSize.html#variant.A4
+    /// [`PageBreak`]: elements/struct.PageBreak.html
+    /// [`PageBreak::with_size`]: elements/struct.PageBreak.html#method.with_size
     pub fn set_paper_size(&mut self, paper_size: impl Into<Size>) {
         self.paper_size = paper_size.into();
     }
@@ -723,6 +875,21 @@ impl Document {
         self.decorator = Some(Box::new(decorator));
     }
 
+    /// Activates or deactivates two-pass rendering for this document.
+    ///
+    /// If two-pass rendering is activated, the document is laid out once without writing it to the
+    /// PDF document to count the pages.  The total page count is then passed to the page decorator
+    /// in the [`PageInfo`][], for example to print “Page 3 of 7” in a header or footer.  This
+    /// requires all elements of the document to support [`Element::reset`][].
+    ///
+    /// Two-pass rendering is deactivated per default.
+    ///
+    /// [`PageInfo`]: struct.PageInfo.html
+    /// [`Element::reset`]: trait.Element.html#method.reset
+    pub fn set_two_pass_rendering(&mut self, two_pass_rendering: bool) {
+        self.two_pass_rendering = two_pass_rendering;
+    }
+
     /// Sets the PDF conformance settings for this document.
     pub fn set_conformance(&mut self, conformance: printpdf::PdfConformance) {
         self.conformance = Some(conformance);
@@ -763,20 +930,130 @@ impl Document {
     pub fn push<E: elements::IntoBoxedElement>(&mut self, element: E) {
         self.root.push(element);
     }
+
+    /// Adds the given element to the document and keeps it on the same page as the beginning of
+    /// the next element, for example for headings.
+    ///
+    /// See [`LinearLayout::push_keep_with_next`][] for more information.
+    ///
+    /// [`LinearLayout::push_keep_with_next`]: elements/struct.LinearLayout.html#method.push_keep_with_next
+    pub fn push_keep_with_next<E: elements::IntoBoxedElement>(&mut self, element: E) {
+        self.root.push_keep_with_next(element);
+    }
     /// add elements to another layer without margins
+    #[deprecated(note = "use Document::push_overlay with a PositionedElement instead")]
     pub fn extra_push<E: elements::IntoBoxedElement>(&mut self, element: E) {
         self.extra_layout.push(element);
     }
+
+    /// Adds the given positioned element to the selected pages of the document.
+    ///
+    /// The element is drawn at a fixed position on every page that matches the given selector,
+    /// independent of the document content.  See [`PositionedElement`][] for more information.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rckive_genpdf::{elements, Anchor, Corner, PageKind, PositionedElement};
+    /// let font_family = rckive_genpdf::fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load font family");
+    /// let mut doc = rckive_genpdf::Document::new(font_family);
+    /// let logo = elements::Paragraph::new("Logo");
+    /// doc.push_overlay(
+    ///     PageKind::First,
+    ///     PositionedElement::new(logo, Anchor::PageCorner(Corner::TopRight), (30, 10))
+    ///         .with_offset((10, 10)),
+    /// );
+    /// ```
+    ///
+    /// [`PositionedElement`]: struct.PositionedElement.html
+    pub fn push_overlay(&mut self, pages: impl Into<PageSelector>, element: PositionedElement) {
+        self.overlays.push((pages.into(), element));
+        self.overlays.sort_by_key(|(_, element)| element.z_order);
+    }
     
+    /// Adds the given watermark to the selected pages of the document.
+    ///
+    /// The watermark fills the whole page and is drawn behind or above the document content
+    /// depending on its z order, see [`Watermark::set_z_order`][].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rckive_genpdf::{elements, style, PageSelector};
+    /// let font_family = rckive_genpdf::fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load font family");
+    /// let mut doc = rckive_genpdf::Document::new(font_family);
+    /// doc.push_watermark(
+    ///     PageSelector::All,
+    ///     elements::Watermark::text("DRAFT")
+    ///         .with_color(style::Color::Rgb(255, 0, 0))
+    ///         .with_opacity(0.2),
+    /// );
+    /// ```
+    ///
+    /// [`Watermark::set_z_order`]: elements/struct.Watermark.html#method.set_z_order
+    pub fn push_watermark(
+        &mut self,
+        pages: impl Into<PageSelector>,
+        watermark: elements::Watermark,
+    ) {
+        let z_order = watermark.z_order();
+        let element = PositionedElement::filling(watermark, Anchor::PageCorner(Corner::TopLeft));
+        self.push_overlay(pages, element.with_z_order(z_order));
+    }
+
     /// Renders this document into a PDF file and writes it to the given writer.
     ///
     /// The given writer is always wrapped in a buffered writer.  For details on the rendering
     /// process, see the [Rendering Process section of the crate
     /// documentation](index.html#rendering-process).
     pub fn render(mut self, w: impl io::Write) -> Result<(), error::Error> {
+        let mut passes = 1;
+        loop {
+            let total = if self.two_pass_rendering {
+                Some(self.count_pages()?)
+            } else {
+                None
+            };
+            let mut renderer = self.create_renderer()?;
+            let mut number = 1;
+            let mut paper_size = self.paper_size;
+            loop {
+                let area = renderer.last_page().last_layer().area();
+                let result = self.render_page(area, PageInfo::new(number, total))?;
+                if result.has_more {
+                    paper_size = self.context.next_page_size.get().unwrap_or(paper_size);
+                    renderer.add_page(paper_size);
+                    number += 1;
+                } else {
+                    break;
+                }
+            }
+
+            // Render the document again if the headings used by the elements have changed.
+            if !self.context.finish_pass() {
+                return renderer.write(w);
+            } else if passes == MAX_RENDERING_PASSES {
+                return Err(error::Error::new(
+                    format!(
+                        "The headings of the document are still changing after {} rendering passes",
+                        MAX_RENDERING_PASSES
+                    ),
+                    error::ErrorKind::UnstableLayout,
+                ));
+            }
+            self.root.reset();
+            self.extra_layout.reset();
+            passes += 1;
+        }
+    }
+
+    /// Creates a new renderer with the settings of this document and loads the fonts.
+    fn create_renderer(&mut self) -> Result<render::Renderer, error::Error> {
         let mut renderer = render::Renderer::new(self.paper_size, &self.title)?;
-        if let Some(conformance) = self.conformance {
-            renderer = renderer.with_conformance(conformance);
+        if let Some(conformance) = &self.conformance {
+            renderer = renderer.with_conformance(conformance.clone());
         }
         if let Some(creation_date) = self.creation_date {
             renderer = renderer.with_creation_date(creation_date);
@@ -784,37 +1061,122 @@ impl Document {
         if let Some(modification_date) = self.modification_date {
             renderer = renderer.with_modification_date(modification_date);
         }
+        renderer.set_links(self.links.clone());
         self.context.font_cache.load_pdf_fonts(&renderer)?;
-        loop {
-            let mut area = renderer.last_page().last_layer().area();
-            let mut area2 = renderer.last_page().last_layer().area();
-            if let Some(decorator) = &mut self.decorator {
-                area = decorator.decorate_page(&self.context, area, area2.clone(), self.style, 
-                                               self.page_frame, self.page_frame_line_style.clone(),
-                                               self.header_frame, self.header_frame_line_style,
-                                               self.footer_frame, self.footer_frame_line_style,
-                                               self.rec_footer, self.page_frame_width_offset)?;
-            }
-            // add multipurpose extra for load from json
-            if self.extra_layout.is_renderable() {
-                let mut area3 = renderer.last_page().last_layer().area();
-                let _result2 = self.extra_layout.render(&self.context, area3.clone(), self.style)?;   
-            }
+        Ok(renderer)
+    }
 
-            let result = self.root.render(&self.context, area, self.style)?;
+    /// Decorates the page with the given area and renders as much of the document content as
+    /// possible to it.
+    fn render_page(
+        &mut self,
+        page_area: render::Area<'_>,
+        page: PageInfo,
+    ) -> Result<RenderResult, error::Error> {
+        self.context.page = page;
+        self.context.next_page_size.set(None);
+        // The overlays behind the content are drawn on the first layer of the page, so the
+        // decorations and the content are moved to the next layer if there are any.
+        let base_area = page_area;
+        let has_background = self.has_overlays(page, false);
+        let page_area = if has_background {
+            base_area.next_layer()
+        } else {
+            base_area.clone()
+        };
+        let mut area = page_area.clone();
+        if let Some(decorator) = &mut self.decorator {
+            let page_context = PageContext {
+                page,
+                paper_size: page_area.size(),
+                style: self.style,
+                frames: self.frames,
+                title: &self.title,
+                user_data: self.user_data.as_deref(),
+            };
+            area = decorator.decorate_page(&self.context, area, &page_context)?;
+        }
+        self.context.content_height = Some(area.size().height);
+        // add multipurpose extra for load from json
+        if !page_area.is_measuring() && self.extra_layout.is_renderable() {
+            self.extra_layout
+                .render(&self.context, page_area.clone(), self.style)?;
+        }
+
+        let content_area = area.clone();
+        if has_background {
+            let background_area = content_area.with_layer_of(&base_area);
+            self.render_overlays(&base_area, &background_area, false)?;
+        }
+        let result = self.root.render(&self.context, area, self.style)?;
+        self.render_overlays(&page_area, &content_area, true)?;
+        if result.has_more && result.size == Size::new(0, 0) {
+            return Err(error::Error::new(
+                "Could not fit an element on a new page",
+                error::ErrorKind::PageSizeExceeded,
+            ));
+        }
+        Ok(result)
+    }
+
+    /// Returns whether there are overlays for the given page that are drawn above the document
+    /// content or, if `above` is false, behind it.
+    fn has_overlays(&self, page: PageInfo, above: bool) -> bool {
+        self.overlays
+            .iter()
+            .any(|(pages, element)| (element.z_order >= 0) == above && pages.matches(page))
+    }
+
+    /// Renders the overlays for the current page that are drawn above the document content or,
+    /// if `above` is false, behind it.
+    ///
+    /// The overlays above the content are drawn on the next layer of the given areas, the
+    /// overlays behind the content on their layer, which has to be below the layer of the page
+    /// decorations.  Overlays are not rendered on measuring areas.
+    fn render_overlays(
+        &mut self,
+        page_area: &render::Area<'_>,
+        content_area: &render::Area<'_>,
+        above: bool,
+    ) -> Result<(), error::Error> {
+        let page = self.context.page;
+        let mut overlays = self
+            .overlays
+            .iter_mut()
+            .filter(|(pages, element)| (element.z_order >= 0) == above && pages.matches(page))
+            .map(|(_, element)| element)
+            .peekable();
+        if page_area.is_measuring() || overlays.peek().is_none() {
+            return Ok(());
+        }
+        let (page_area, content_area) = if above {
+            (page_area.next_layer(), content_area.next_layer())
+        } else {
+            (page_area.clone(), content_area.clone())
+        };
+        for element in overlays {
+            element.render(&self.context, &page_area, &content_area, self.style)?;
+        }
+        Ok(())
+    }
+
+    /// Lays out the document on measuring areas, returns the number of pages and resets the
+    /// document content.
+    fn count_pages(&mut self) -> Result<usize, error::Error> {
+        let mut number = 1;
+        let mut paper_size = self.paper_size;
+        loop {
+            let area = render::Area::page_layout(paper_size);
+            let result = self.render_page(area, PageInfo::new(number, None))?;
             if result.has_more {
-                if result.size == Size::new(0, 0) {
-                    return Err(error::Error::new(
-                        "Could not fit an element on a new page",
-                        error::ErrorKind::PageSizeExceeded,
-                    ));
-                }
-                renderer.add_page(self.paper_size);
+                paper_size = self.context.next_page_size.get().unwrap_or(paper_size);
+                number += 1;
             } else {
                 break;
             }
         }
-        renderer.write(w)
+        self.root.reset();
+        Ok(number)
     }
 
     /// Renders this document into a PDF file at the given path.
@@ -864,6 +1226,317 @@ pub struct RenderResult {
     pub has_more: bool,
 }
 
+/// Information about the page that is currently rendered.
+///
+/// This struct is passed to the [`PageDecorator`][] for every page as part of the
+/// [`PageContext`][].
+///
+/// [`PageDecorator`]: trait.PageDecorator.html
+/// [`PageContext`]: struct.PageContext.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PageInfo {
+    /// The number of the page, starting with 1.
+    pub number: usize,
+    /// The total number of pages in the document.
+    ///
+    /// The total number of pages is only known if two-pass rendering has been activated with
+    /// [`Document::set_two_pass_rendering`][].  It is always `None` during the first pass.
+    ///
+    /// [`Document::set_two_pass_rendering`]: struct.Document.html#method.set_two_pass_rendering
+    pub total: Option<usize>,
+}
+
+impl PageInfo {
+    /// Creates a new page info with the given page number and total number of pages.
+    pub fn new(number: usize, total: Option<usize>) -> PageInfo {
+        PageInfo { number, total }
+    }
+}
+
+/// The frames that are drawn around the content, the header and the footer of a page.
+///
+/// The frames are set with [`Document::set_page_frames`][] and the corresponding setters of the
+/// [`Document`][], and drawn by the [`SimplePageDecorator`][].
+///
+/// [`Document`]: struct.Document.html
+/// [`Document::set_page_frames`]: struct.Document.html#method.set_page_frames
+/// [`SimplePageDecorator`]: struct.SimplePageDecorator.html
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PageFrames {
+    /// The line style of the frame around the page content, or `None` if no frame is drawn.
+    pub page: Option<style::LineStyle>,
+    /// The line style of the frame around the page header, or `None` if no frame is drawn.
+    pub header: Option<style::LineStyle>,
+    /// The line style of the frame around the page footer, or `None` if no frame is drawn.
+    pub footer: Option<style::LineStyle>,
+    /// The horizontal distance between the frames and the content.
+    pub width_offset: Mm,
+}
+
+/// The settings of the document that are passed to the [`PageDecorator`][] for every page.
+///
+/// New fields may be added to this struct without a breaking change, so it can only be created
+/// with [`PageContext::new`][] outside of this crate.
+///
+/// [`PageDecorator`]: trait.PageDecorator.html
+/// [`PageContext::new`]: #method.new
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct PageContext<'a> {
+    /// The number of the page and the total number of pages.
+    pub page: PageInfo,
+    /// The paper size of the page.
+    ///
+    /// This size can differ between the pages of a document, see [`PageBreak::with_size`][].
+    ///
+    /// [`PageBreak::with_size`]: elements/struct.PageBreak.html#method.with_size
+    pub paper_size: Size,
+    /// The default style of the document.
+    pub style: style::Style,
+    /// The frames that should be drawn on the page.
+    pub frames: PageFrames,
+    /// The title of the document.
+    pub title: &'a str,
+    user_data: Option<&'a dyn any::Any>,
+}
+
+impl<'a> PageContext<'a> {
+    /// Creates a new page context for the given page and paper size with the default style, no
+    /// frames, an empty title and no user data.
+    pub fn new(page: PageInfo, paper_size: impl Into<Size>) -> PageContext<'a> {
+        PageContext {
+            page,
+            paper_size: paper_size.into(),
+            style: style::Style::new(),
+            frames: PageFrames::default(),
+            title: "",
+            user_data: None,
+        }
+    }
+
+    /// Sets the user data of this page context, see [`Document::set_user_data`][].
+    ///
+    /// [`Document::set_user_data`]: struct.Document.html#method.set_user_data
+    pub fn set_user_data(&mut self, data: &'a dyn any::Any) {
+        self.user_data = Some(data);
+    }
+
+    /// Returns the user data set with [`Document::set_user_data`][] if it has the type `T`.
+    ///
+    /// [`Document::set_user_data`]: struct.Document.html#method.set_user_data
+    pub fn user_data<T: any::Any>(&self) -> Option<&'a T> {
+        self.user_data.and_then(|data| data.downcast_ref())
+    }
+}
+
+/// Information about a heading that has been rendered, see [`Context::headings`][].
+///
+/// [`Context::headings`]: struct.Context.html#method.headings
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct HeadingInfo {
+    /// The title of the heading.
+    pub title: String,
+    /// The level of the heading, starting with 1 for top-level headings.
+    pub level: usize,
+    /// The number of the page that the heading has been rendered on, starting with 1.
+    pub page: usize,
+    /// The name of the named destination at the heading, see [`style::Link::Destination`][].
+    ///
+    /// [`style::Link::Destination`]: style/enum.Link.html#variant.Destination
+    pub destination: String,
+}
+
+/// A selection of pages of a document, see [`Document::push_overlay`][].
+///
+/// [`Document::push_overlay`]: struct.Document.html#method.push_overlay
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PageSelector {
+    /// All pages of the document.
+    All,
+    /// The pages of the given kind.
+    Kind(PageKind),
+    /// The pages with the given page numbers, starting with 1.
+    Pages(Vec<usize>),
+}
+
+impl PageSelector {
+    /// Returns `true` if the given page is part of this selection.
+    pub fn matches(&self, page: PageInfo) -> bool {
+        match self {
+            PageSelector::All => true,
+            PageSelector::Kind(kind) => kind.matches(page),
+            PageSelector::Pages(pages) => pages.contains(&page.number),
+        }
+    }
+}
+
+impl From<PageKind> for PageSelector {
+    fn from(kind: PageKind) -> PageSelector {
+        PageSelector::Kind(kind)
+    }
+}
+
+/// A corner of a rectangle.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Corner {
+    /// The upper left corner.
+    TopLeft,
+    /// The upper right corner.
+    TopRight,
+    /// The lower left corner.
+    BottomLeft,
+    /// The lower right corner.
+    BottomRight,
+}
+
+/// The reference point of a [`PositionedElement`][].
+///
+/// [`PositionedElement`]: struct.PositionedElement.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Anchor {
+    /// A corner of the page, ignoring the margins, header and footer.
+    PageCorner(Corner),
+    /// A corner of the content area of the page as returned by the [`PageDecorator`][].
+    ///
+    /// [`PageDecorator`]: trait.PageDecorator.html
+    ContentBox(Corner),
+}
+
+/// An element that is drawn at a fixed position of a page, see [`Document::push_overlay`][].
+///
+/// The element is placed relative to its [`Anchor`][]:  The corner of the element that matches
+/// the anchor corner is moved by the offset towards the center of the page, so the offset is
+/// always measured from the edges of the page or content area that meet at the anchor corner.
+/// The element is rendered with the given size, or with the size of the page or content area if
+/// it has been created with [`filling`][], and everything it draws outside of that size is clipped.
+///
+/// Positioned elements are drawn on every selected page without affecting the layout of the
+/// document content.  Elements with a negative z order are drawn on a separate layer behind the
+/// page decorations and the document content, all others are drawn above them on a separate
+/// layer.  Elements with a higher z order are drawn above elements with a lower z order.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, Anchor, Corner, PositionedElement};
+/// let stamp = PositionedElement::new(
+///     elements::Paragraph::new("Approved"),
+///     Anchor::ContentBox(Corner::BottomRight),
+///     (40, 10),
+/// )
+/// .with_offset((5, 5))
+/// .with_z_order(1);
+/// ```
+///
+/// [`Document::push_overlay`]: struct.Document.html#method.push_overlay
+/// [`Anchor`]: enum.Anchor.html
+/// [`filling`]: #method.filling
+pub struct PositionedElement {
+    element: Box<dyn Element>,
+    anchor: Anchor,
+    offset: Position,
+    size: Option<Size>,
+    z_order: i32,
+}
+
+impl PositionedElement {
+    /// Creates a new positioned element with the given anchor and size.
+    pub fn new<E: elements::IntoBoxedElement>(
+        element: E,
+        anchor: Anchor,
+        size: impl Into<Size>,
+    ) -> PositionedElement {
+        PositionedElement {
+            element: element.into_boxed_element(),
+            anchor,
+            offset: Position::default(),
+            size: Some(size.into()),
+            z_order: 0,
+        }
+    }
+
+    /// Creates a new positioned element that has the size of the page or content area of the
+    /// given anchor.
+    pub fn filling<E: elements::IntoBoxedElement>(element: E, anchor: Anchor) -> PositionedElement {
+        PositionedElement {
+            element: element.into_boxed_element(),
+            anchor,
+            offset: Position::default(),
+            size: None,
+            z_order: 0,
+        }
+    }
+
+    /// Sets the offset of this element from its anchor.
+    pub fn set_offset(&mut self, offset: impl Into<Position>) {
+        self.offset = offset.into();
+    }
+
+    /// Sets the offset of this element from its anchor and returns the element.
+    pub fn with_offset(mut self, offset: impl Into<Position>) -> Self {
+        self.set_offset(offset);
+        self
+    }
+
+    /// Sets the z order of this element.
+    ///
+    /// Elements with a negative z order are drawn behind the page decorations and the document
+    /// content.  Per default, the z order is zero.
+    pub fn set_z_order(&mut self, z_order: i32) {
+        self.z_order = z_order;
+    }
+
+    /// Sets the z order of this element and returns the element.
+    pub fn with_z_order(mut self, z_order: i32) -> Self {
+        self.set_z_order(z_order);
+        self
+    }
+
+    /// Returns the area and the size of this element in the given page and content areas.
+    fn get_area<'a>(
+        &self,
+        page_area: &render::Area<'a>,
+        content_area: &render::Area<'a>,
+    ) -> (render::Area<'a>, Size) {
+        let (mut area, corner) = match self.anchor {
+            Anchor::PageCorner(corner) => (page_area.clone(), corner),
+            Anchor::ContentBox(corner) => (content_area.clone(), corner),
+        };
+        let size = area.size();
+        let element_size = self.size.unwrap_or(size);
+        let x = match corner {
+            Corner::TopLeft | Corner::BottomLeft => self.offset.x,
+            Corner::TopRight | Corner::BottomRight => {
+                size.width - element_size.width - self.offset.x
+            }
+        };
+        let y = match corner {
+            Corner::TopLeft | Corner::TopRight => self.offset.y,
+            Corner::BottomLeft | Corner::BottomRight => {
+                size.height - element_size.height - self.offset.y
+            }
+        };
+        area.add_offset(Position::new(x, y));
+        (area, element_size)
+    }
+
+    /// Renders this element on the page with the given page and content areas and resets it so
+    /// that it can be rendered again on the next page.
+    fn render(
+        &mut self,
+        context: &Context,
+        page_area: &render::Area<'_>,
+        content_area: &render::Area<'_>,
+        style: style::Style,
+    ) -> Result<(), error::Error> {
+        let (area, size) = self.get_area(page_area, content_area);
+        let element = &mut self.element;
+        area.with_clip(size, |area| element.render(context, area, style))?;
+        self.element.reset();
+        Ok(())
+    }
+}
+
 /// Prepares a page of a document.
 ///
 /// If you set an implementation of this trait for a [`Document`][] using the
@@ -879,44 +1552,108 @@ pub trait PageDecorator {
     /// Prepares the page with the given area before it is filled with the document content and
     /// returns the writable area of the page.
     ///
-    /// The returned area will be passed to the document content.
+    /// The returned area will be passed to the document content.  If two-pass rendering is
+    /// activated, this method is also called with measuring areas during the first pass.  The
+    /// page number and the settings of the document are passed in the [`PageContext`][].
+    ///
+    /// [`PageContext`]: struct.PageContext.html
     fn decorate_page<'a>(
         &mut self,
         context: &Context,
         area: render::Area<'a>,
-        area_footer: render::Area<'a>,
-        style: style::Style,
-        //add page frame
-        page_frame: bool,
-        page_frame_line_style: style::LineStyle,
-        header_frame: bool,
-        header_frame_line_style: style::LineStyle,
-        footer_frame: bool,
-        footer_frame_line_style: style::LineStyle,
-        rec_footer: (Position, Position),
-        page_frame_width_offset: Mm,
+        page_context: &PageContext<'_>,
     ) -> Result<render::Area<'a>, error::Error>;
 }
 
-type HeaderCallback = Box<dyn Fn(usize) -> Box<dyn Element>>;
+type HeaderCallback = Box<dyn Fn(PageInfo) -> Box<dyn Element>>;
+
+/// A kind of pages that can be decorated differently by the [`SimplePageDecorator`][].
+///
+/// [`SimplePageDecorator`]: struct.SimplePageDecorator.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PageKind {
+    /// The first page of the document.
+    First,
+    /// The pages with an odd page number, i. e. the right pages of a document that is printed on
+    /// both sides.
+    Odd,
+    /// The pages with an even page number, i. e. the left pages of a document that is printed on
+    /// both sides.
+    Even,
+}
+
+impl PageKind {
+    /// Returns `true` if the given page is of this kind.
+    pub fn matches(self, page: PageInfo) -> bool {
+        match (self, page.number % 2) {
+            (PageKind::First, _) => page.number == 1,
+            (PageKind::Odd, 1) | (PageKind::Even, 0) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The settings of the [`SimplePageDecorator`][] for a [`PageKind`][].
+///
+/// `None` means that the setting for all pages is used.  The header and footer callbacks are set
+/// to `Some(None)` to hide the header or footer.
+///
+/// [`SimplePageDecorator`]: struct.SimplePageDecorator.html
+/// [`PageKind`]: enum.PageKind.html
+#[derive(Default)]
+struct PageDecoration {
+    margins: Option<Margins>,
+    header_cb: Option<Option<HeaderCallback>>,
+    footer_cb: Option<Option<HeaderCallback>>,
+}
 
-/// Prepares a page of a document with margins and a header.
+/// Prepares a page of a document with margins, a header and a footer.
 ///
 /// Per default, this decorator does not modify the page.  If margins have been set with the
 /// [`set_margins`][] method, they are applied to every page.  If a header callback is configured
 /// with the [`set_header`][] method, it will be called for every page and its return value will be
-/// rendered at the beginning of the page (after the margins have been applied).
+/// rendered at the beginning of the page (after the margins have been applied).  Similarly, the
+/// return value of the footer callback configured with the [`set_footer`][] method is rendered at
+/// the end of the page.  The space used by the header and the footer is not available for the
+/// document content.
+///
+/// The margins, the header and the footer can be changed for the first page, the odd and the even
+/// pages (see [`PageKind`][]) with the [`set_page_margins`][], [`set_page_header`][] and
+/// [`set_page_footer`][] methods, and the header and the footer can be hidden with the
+/// [`hide_page_header`][] and [`hide_page_footer`][] methods.  The settings for the first page
+/// take precedence over the settings for the odd pages, and the settings for the odd and even
+/// pages take precedence over the settings for all pages.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, Margins, PageKind, SimplePageDecorator};
+///
+/// let margins = Margins::trbl(10, 10, 10, 20);
+/// let mut decorator = SimplePageDecorator::new();
+/// decorator.set_margins(margins);
+/// decorator.set_page_margins(PageKind::Even, margins.mirrored());
+/// decorator.set_header(|page| elements::Paragraph::new(format!("Page {}", page.number)));
+/// decorator.hide_page_header(PageKind::First);
+/// ```
 ///
 /// [`set_margins`]: #method.set_margins
 /// [`set_header`]: #method.set_header
-/// ['set_footer']: #method.set_footer
+/// [`set_footer`]: #method.set_footer
+/// [`set_page_margins`]: #method.set_page_margins
+/// [`set_page_header`]: #method.set_page_header
+/// [`set_page_footer`]: #method.set_page_footer
+/// [`hide_page_header`]: #method.hide_page_header
+/// [`hide_page_footer`]: #method.hide_page_footer
+/// [`PageKind`]: enum.PageKind.html
 #[derive(Default)]
 pub struct SimplePageDecorator {
-    page: usize,
     margins: Option<Margins>,
     header_cb: Option<HeaderCallback>,
-    // add footer experimental with layout orphan
     footer_cb: Option<HeaderCallback>,
+    first: PageDecoration,
+    odd: PageDecoration,
+    even: PageDecoration,
 }
 
 impl SimplePageDecorator {
@@ -934,27 +1671,105 @@ impl SimplePageDecorator {
 
     /// Sets the header generator for this document.
     ///
-    /// The given closure will be called once per page.  Its argument is the page number (starting
-    /// with 1), and its return value will be rendered at the top of the page.  The document
+    /// The given closure will be called once per page.  Its argument is the [`PageInfo`][] with
+    /// the page number (starting with 1) and the total number of pages (if two-pass rendering is
+    /// activated), and its return value will be rendered at the top of the page.  The document
     /// content will start directly after the element.
+    ///
+    /// [`PageInfo`]: struct.PageInfo.html
     pub fn set_header<F, E>(&mut self, cb: F)
     where
-        F: Fn(usize) -> E + 'static,
+        F: Fn(PageInfo) -> E + 'static,
         E: Element + 'static,
     {
         // We manually box the return type of the callback so that it is easier to write closures.
         self.header_cb = Some(Box::new(move |page| Box::new(cb(page))));
     }
     
-    ///experimental  footer, The idea is to use an orphaned linear layout
+    /// Sets the footer generator for this document.
+    ///
+    /// The given closure will be called once per page.  Like the header, it receives the
+    /// [`PageInfo`][] of the page, and its return value is measured (see [`Element::measure`][])
+    /// and rendered at the bottom of the page (after the margins have been applied).  The document
+    /// content ends directly before the element.
+    ///
+    /// [`PageInfo`]: struct.PageInfo.html
+    /// [`Element::measure`]: trait.Element.html#method.measure
     pub fn set_footer<F, E>(&mut self, cb: F)
     where
-        F: Fn(usize) -> E + 'static,
+        F: Fn(PageInfo) -> E + 'static,
         E: Element + 'static,
     {
         // We manually box the return type of the callback so that it is easier to write closures.
         self.footer_cb = Some(Box::new(move |page| Box::new(cb(page))));
     }
+
+    /// Sets the margins for the given kind of pages.
+    ///
+    /// These margins are used instead of the margins set with [`set_margins`][].
+    ///
+    /// [`set_margins`]: #method.set_margins
+    pub fn set_page_margins(&mut self, kind: PageKind, margins: impl Into<Margins>) {
+        self.decoration_mut(kind).margins = Some(margins.into());
+    }
+
+    /// Sets the header generator for the given kind of pages.
+    ///
+    /// This header is used instead of the header set with [`set_header`][].
+    ///
+    /// [`set_header`]: #method.set_header
+    pub fn set_page_header<F, E>(&mut self, kind: PageKind, cb: F)
+    where
+        F: Fn(PageInfo) -> E + 'static,
+        E: Element + 'static,
+    {
+        self.decoration_mut(kind).header_cb = Some(Some(Box::new(move |page| Box::new(cb(page)))));
+    }
+
+    /// Sets the footer generator for the given kind of pages.
+    ///
+    /// This footer is used instead of the footer set with [`set_footer`][].
+    ///
+    /// [`set_footer`]: #method.set_footer
+    pub fn set_page_footer<F, E>(&mut self, kind: PageKind, cb: F)
+    where
+        F: Fn(PageInfo) -> E + 'static,
+        E: Element + 'static,
+    {
+        self.decoration_mut(kind).footer_cb = Some(Some(Box::new(move |page| Box::new(cb(page)))));
+    }
+
+    /// Hides the header on the given kind of pages, for example on a cover page.
+    pub fn hide_page_header(&mut self, kind: PageKind) {
+        self.decoration_mut(kind).header_cb = Some(None);
+    }
+
+    /// Hides the footer on the given kind of pages.
+    pub fn hide_page_footer(&mut self, kind: PageKind) {
+        self.decoration_mut(kind).footer_cb = Some(None);
+    }
+
+    fn decoration_mut(&mut self, kind: PageKind) -> &mut PageDecoration {
+        match kind {
+            PageKind::First => &mut self.first,
+            PageKind::Odd => &mut self.odd,
+            PageKind::Even => &mut self.even,
+        }
+    }
+
+    /// Returns the settings for the given page, ordered by precedence.
+    fn decorations(&self, page: PageInfo) -> Vec<&PageDecoration> {
+        let decorations = [
+            (PageKind::First, &self.first),
+            (PageKind::Odd, &self.odd),
+            (PageKind::Even, &self.even),
+        ];
+        decorations
+            .iter()
+            .filter(|(kind, _)| kind.matches(page))
+            .map(|(_, decoration)| *decoration)
+            .collect()
+    }
 }
 
 impl PageDecorator for SimplePageDecorator {
@@ -962,28 +1777,29 @@ impl PageDecorator for SimplePageDecorator {
         &mut self,
         context: &Context,
         mut area: render::Area<'a>,
-        mut area_footer: render::Area<'a>,
-        style: style::Style,
-        page_frame: bool,
-        page_frame_line_style: style::LineStyle,
-        header_frame: bool,
-        header_frame_line_style: style::LineStyle,
-        footer_frame: bool,
-        footer_frame_line_style: style::LineStyle,
-        rec_footer: (Position, Position),
-        page_frame_width_offset: Mm,
+        page_context: &PageContext<'_>,
     ) -> Result<render::Area<'a>, error::Error> {
-        self.page += 1;
-        if let Some(margins) = self.margins {
+        let page = page_context.page;
+        let style = page_context.style;
+        let page_frame_width_offset = page_context.frames.width_offset;
+        let decorations = self.decorations(page);
+        let margins = decorations.iter().find_map(|d| d.margins).or(self.margins);
+        let header_cb = decorations
+            .iter()
+            .find_map(|d| d.header_cb.as_ref())
+            .unwrap_or(&self.header_cb);
+        let footer_cb = decorations
+            .iter()
+            .find_map(|d| d.footer_cb.as_ref())
+            .unwrap_or(&self.footer_cb);
+        if let Some(margins) = margins {
             area.add_margins(margins);
-            let footer_margins = Margins::trbl(0.0,margins.right(),0.0,margins.left());
-            area_footer.add_margins(footer_margins);            
         }
-        if let Some(cb) = &self.header_cb {
-            let mut element = cb(self.page);
+        if let Some(cb) = header_cb {
+            let mut element = cb(page);
             let result = element.render(context, area.clone(), style)?;
             area.add_offset(Position::new(0, result.size.height));
-            if header_frame {
+            if let Some(header_frame_line_style) = page_context.frames.header {
                 let top_left = Position::new(Mm(0.0) + (page_frame_width_offset * -1.0), result.size.height*-1.0);
                 let top_right = Position::new(area.size().width - (page_frame_width_offset * -1.0), result.size.height*-1.0);
                 let bottom_left = Position::new(Mm(0.0) + (page_frame_width_offset * -1.0), 0.0);
@@ -994,8 +1810,28 @@ impl PageDecorator for SimplePageDecorator {
                         );
             }
         }
+        if let Some(fcb) = footer_cb {
+            let mut element = fcb(page);
+            // The footer is measured first so that it can be aligned with the bottom of the page.
+            let height = element.measure(context, area.clone(), style)?.size.height;
+            let body_height = (area.size().height - height).max(Mm(0.0));
+            let mut footer_area = area.clone();
+            footer_area.add_offset(Position::new(0, body_height));
+            element.render(context, footer_area.clone(), style)?;
+            area.set_height(body_height);
+            if let Some(footer_frame_line_style) = page_context.frames.footer {
+                let top_left = Position::new(Mm(0.0) + (page_frame_width_offset * -1.0), 0.0);
+                let top_right = Position::new(footer_area.size().width - (page_frame_width_offset * -1.0), 0.0);
+                let bottom_left = Position::new(Mm(0.0) + (page_frame_width_offset * -1.0), height);
+                let bottom_right = Position::new(footer_area.size().width - (page_frame_width_offset * -1.0), height);
+                footer_area.draw_line(
+                            vec![bottom_right, top_right, top_left, bottom_left, bottom_right],
+                            footer_frame_line_style,
+                        );
+            }
+        }
         // Draw the page frame.
-        if page_frame {
+        if let Some(page_frame_line_style) = page_context.frames.page {
             let mut frame_area = area.clone();  
             let top_left = Position::new(Mm(0.0) + (page_frame_width_offset * -1.0), 0);
             let top_right = Position::new(frame_area.size().width - (page_frame_width_offset * -1.0), 0);
@@ -1006,21 +1842,6 @@ impl PageDecorator for SimplePageDecorator {
                         page_frame_line_style,
                     );
         }
-        if let Some(fcb) = &self.footer_cb {
-            let mut element = fcb(self.page);
-            let result = element.render(context, area_footer.clone(), style)?;
-            area_footer.add_offset(Position::new(0, result.size.height));
-            if footer_frame {
-                let top_left = Position::new(Mm(0.0) + (page_frame_width_offset * -1.0), rec_footer.0.y);
-                let top_right = Position::new(area_footer.size().width - (page_frame_width_offset * -1.0), rec_footer.0.y);
-                let bottom_left = Position::new(Mm(0.0) + (page_frame_width_offset * -1.0), rec_footer.0.y + rec_footer.1.y);
-                let bottom_right = Position::new(area_footer.size().width - (page_frame_width_offset * -1.0), rec_footer.0.y + rec_footer.1.y);
-                area_footer.draw_line(
-                            vec![bottom_right, top_right, top_left, bottom_left, bottom_right],
-                            footer_frame_line_style,
-                        );
-            }                        
-        }
         Ok(area)
     }
 }
@@ -1058,7 +1879,8 @@ pub trait Element {
     ///   to this method is always the start of the rendering process, and subsequent calls are
     ///   always continuations of the same rendering process.  This means that the element does not
     ///   have to reset its state after it has processed all content, and it is allowed to drop
-    ///   content that has already been rendered.
+    ///   content that has already been rendered.  The only exception is a call to [`reset`][],
+    ///   which starts a new rendering process.
     /// - If a call to this method returns an `Err` value, it will not be called again.
     /// - After the first call, the method will only be called again if the `has_more` of the last
     ///   [`RenderResult`][] was set to true.
@@ -1070,6 +1892,7 @@ pub trait Element {
     /// [`render`]: struct.Document.html#method.render
     /// [`render_to_file`]: struct.Document.html#method.render_to_file
     /// [`RenderResult`]: struct.RenderResult.html
+    /// [`reset`]: #method.reset
     fn render(
         &mut self,
         context: &Context,
@@ -1077,6 +1900,44 @@ pub trait Element {
         style: style::Style,
     ) -> Result<RenderResult, error::Error>;
 
+    /// Resets the rendering state of this element so that the next call to [`render`][] starts a
+    /// new rendering process.
+    ///
+    /// Some containers render their children into a measuring area (see
+    /// [`render::Area::measuring`][]) to determine their size before rendering them to the
+    /// document, and call this method afterwards.  Elements that keep state between calls to
+    /// [`render`][] should restore their initial state here.  The default implementation does
+    /// nothing.
+    ///
+    /// [`render`]: #tymethod.render
+    /// [`render::Area::measuring`]: render/struct.Area.html#method.measuring
+    fn reset(&mut self) {}
+
+    /// Measures this element without writing it to the document.
+    ///
+    /// The element is rendered into a measuring copy of the given area (see
+    /// [`render::Area::to_measuring`][]), so floats that have been placed in the area are taken
+    /// into account.  The returned result is the result that the next call to [`render`][] would
+    /// return for this area.
+    ///
+    /// The rendering state of this element is not changed.  The default implementation renders
+    /// the element and calls [`reset`][] afterwards, so it requires the element to support
+    /// [`reset`][], and it can only be used before the rendering process of this element is
+    /// started.  Elements that keep state between calls to [`render`][] should override it so
+    /// that the remaining content is measured, or return an error if that is not possible.
+    ///
+    /// [`render`]: #tymethod.render
+    /// [`reset`]: #method.reset
+    /// [`render::Area::to_measuring`]: render/struct.Area.html#method.to_measuring
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: style::Style,
+    ) -> Result<RenderResult, error::Error> {
+        measure_and_reset(self, context, area, style)
+    }
+
     /// Draws a frame around this element using the given line style.
     fn framed(self, line_style: impl Into<style::LineStyle>) -> elements::FramedElement<Self>
     where
@@ -1100,8 +1961,53 @@ pub trait Element {
     {
         elements::StyledElement::new(self, style.into())
     }
+
+    /// Turns this element into a clickable link with the given target.
+    fn linked(self, link: style::Link) -> elements::LinkedElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::LinkedElement::new(self, link)
+    }
+
+    /// Adds a named destination with the given name at the beginning of this element.
+    fn anchored(self, name: impl Into<String>) -> elements::AnchoredElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::AnchoredElement::new(self, name)
+    }
+
+    /// Moves this element to the next page if it does not fit on the current page.
+    fn kept_together(self) -> elements::KeepTogether<Self>
+    where
+        Self: Sized,
+    {
+        elements::KeepTogether::new(self)
+    }
+}
+
+/// Renders the given element into a measuring copy of the given area and resets it afterwards.
+///
+/// This is the default implementation of [`Element::measure`][] that can be used by elements that
+/// override it.
+///
+/// [`Element::measure`]: trait.Element.html#method.measure
+pub(crate) fn measure_and_reset<E: Element + ?Sized>(
+    element: &mut E,
+    context: &Context,
+    area: render::Area<'_>,
+    style: style::Style,
+) -> Result<RenderResult, error::Error> {
+    let result = element.render(context, area.to_measuring(), style)?;
+    element.reset();
+    Ok(result)
 }
 
+/// The maximum number of times a document is rendered until the headings are stable, see
+/// [`Context::headings`](struct.Context.html#method.headings).
+const MAX_RENDERING_PASSES: usize = 5;
+
 /// The context for a rendering process.
 ///
 /// This struct stores data that is shared between all elements during the rendering process.
@@ -1119,12 +2025,27 @@ pub struct Context {
     pub hyphenator: Option<hyphenation::Standard>,
     /// skip warning
     pub skip_warning_overflowed: bool,
+    page: PageInfo,
+    content_height: Option<Mm>,
+    next_page_size: cell::Cell<Option<Size>>,
+    headings: cell::RefCell<Vec<HeadingInfo>>,
+    previous_headings: Vec<HeadingInfo>,
+    uses_headings: cell::Cell<bool>,
 }
 
 impl Context {
     #[cfg(not(feature = "hyphenation"))]
     fn new(font_cache: fonts::FontCache) -> Context {
-        Context { font_cache, skip_warning_overflowed: false,}
+        Context {
+            font_cache,
+            skip_warning_overflowed: false,
+            page: PageInfo::default(),
+            content_height: None,
+            next_page_size: Default::default(),
+            headings: Default::default(),
+            previous_headings: Vec::new(),
+            uses_headings: Default::default(),
+        }
     }
 
     #[cfg(feature = "hyphenation")]
@@ -1133,8 +2054,82 @@ impl Context {
             font_cache,
             hyphenator: None,
             skip_warning_overflowed: false,
+            page: PageInfo::default(),
+            content_height: None,
+            next_page_size: Default::default(),
+            headings: Default::default(),
+            previous_headings: Vec::new(),
+            uses_headings: Default::default(),
         }
     }
+
+    /// Returns information about the page that is currently rendered.
+    pub fn page(&self) -> PageInfo {
+        self.page
+    }
+
+    /// Returns the height of the area for the document content on the current page, i. e. the
+    /// height of the page without the margins and the page decorations, if it is known.
+    ///
+    /// Note that the height that is available to an element on an empty page can be smaller, see
+    /// [`render::Area::empty_height`][].
+    ///
+    /// [`render::Area::empty_height`]: render/struct.Area.html#method.empty_height
+    pub fn content_height(&self) -> Option<Mm> {
+        self.content_height
+    }
+
+    /// Sets the paper size of the pages following the current page.
+    ///
+    /// This method is called by elements like the [`PageBreak`][] to change the paper size during
+    /// the rendering process.  The size is used for all following pages until it is changed again.
+    /// If this method is called several times on one page, the last size is used.
+    ///
+    /// [`PageBreak`]: elements/struct.PageBreak.html
+    pub fn set_next_page_size(&self, size: impl Into<Size>) {
+        self.next_page_size.set(Some(size.into()));
+    }
+
+    /// Registers a heading with the given title and level on the current page and returns the
+    /// name of the named destination that should be added at the heading.
+    ///
+    /// This method should only be called if the heading is rendered to the document and not to a
+    /// measuring area, see [`render::Area::is_measuring`][].
+    ///
+    /// [`render::Area::is_measuring`]: render/struct.Area.html#method.is_measuring
+    pub fn register_heading(&self, title: impl Into<String>, level: usize) -> String {
+        let mut headings = self.headings.borrow_mut();
+        let destination = format!("heading-{}", headings.len() + 1);
+        headings.push(HeadingInfo {
+            title: title.into(),
+            level,
+            page: self.page.number,
+            destination: destination.clone(),
+        });
+        destination
+    }
+
+    /// Returns the headings that have been registered during the previous rendering process.
+    ///
+    /// During the first rendering process, no headings are available.  If this method is called
+    /// and the headings registered during the current rendering process differ from the returned
+    /// headings, the document is rendered again.  If the headings do not stabilize, rendering the
+    /// document fails with [`ErrorKind::UnstableLayout`][].
+    ///
+    /// [`ErrorKind::UnstableLayout`]: error/enum.ErrorKind.html#variant.UnstableLayout
+    pub fn headings(&self) -> &[HeadingInfo] {
+        self.uses_headings.set(true);
+        &self.previous_headings
+    }
+
+    /// Finishes a rendering process and returns `true` if the document has to be rendered again
+    /// because the headings have changed.
+    fn finish_pass(&mut self) -> bool {
+        let headings = self.headings.take();
+        let is_stale = self.uses_headings.take() && headings != self.previous_headings;
+        self.previous_headings = headings;
+        is_stale
+    }
 }
 
 #[cfg(test)]
@@ -1165,6 +2160,605 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_two_pass_rendering() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let font_family = super::fonts::from_files(
+            "./tests/files/liberation",
+            "LiberationSans",
+            Some(super::fonts::Builtin::Helvetica),
+        )
+        .expect("Failed to load the default font family");
+        let mut doc = super::Document::new(font_family);
+        let pages = Rc::new(RefCell::new(Vec::new()));
+        let mut decorator = super::SimplePageDecorator::new();
+        let header_pages = pages.clone();
+        decorator.set_header(move |page| {
+            header_pages.borrow_mut().push(page);
+            super::elements::Break::new(1.0)
+        });
+        doc.set_page_decorator(decorator);
+        doc.set_two_pass_rendering(true);
+        doc.push(super::elements::Paragraph::new("first"));
+        doc.push(super::elements::PageBreak::new());
+        doc.push(super::elements::Paragraph::new("second"));
+        doc.render(std::io::sink()).expect("Failed to render document");
+
+        let pages = pages.borrow();
+        assert_eq!(
+            &[
+                super::PageInfo::new(1, None),
+                super::PageInfo::new(2, None),
+                super::PageInfo::new(1, Some(2)),
+                super::PageInfo::new(2, Some(2)),
+            ],
+            pages.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_lazy_font_embedding() {
+        use super::Element as _;
+
+        let font_family =
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                .expect("Failed to load the default font family");
+        let mut doc = super::Document::new(font_family);
+        doc.set_two_pass_rendering(true);
+        doc.push(super::elements::Paragraph::new("regular"));
+        doc.push(super::elements::Paragraph::new("bold").styled(super::style::Effect::Bold));
+        doc.push(super::elements::Paragraph::new("").styled(super::style::Effect::Italic));
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load rendered document");
+        let fonts = pdf
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .filter_map(|dict| dict.get(b"Subtype").and_then(lopdf::Object::as_name).ok())
+            .filter(|subtype| subtype == b"Type0")
+            .count();
+        assert_eq!(2, fonts);
+    }
+
+    #[test]
+    fn test_renderer_settings_after_loading_fonts() {
+        let font_family =
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                .expect("Failed to load the default font family");
+        let mut font_cache = super::fonts::FontCache::new(font_family);
+        let renderer = super::render::Renderer::new(super::PaperSize::A4, "Settings")
+            .expect("Failed to create renderer");
+        font_cache
+            .load_pdf_fonts(&renderer)
+            .expect("Failed to load PDF fonts");
+        let date = printpdf::OffsetDateTime::from_unix_timestamp(1_000_000_000)
+            .expect("Invalid timestamp");
+        let renderer = renderer
+            .with_conformance(printpdf::PdfConformance::Custom(Default::default()))
+            .with_creation_date(date)
+            .with_modification_date(date);
+
+        let area = renderer.first_page().first_layer().area();
+        area.print_str(
+            &font_cache,
+            super::Position::default(),
+            super::style::Style::new(),
+            "text",
+        )
+        .expect("Failed to print text");
+        let mut data = Vec::new();
+        renderer.write(&mut data).expect("Failed to write document");
+
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load rendered document");
+        let info = pdf.trailer.get(b"Info").unwrap().as_reference().unwrap();
+        let info = pdf.get_dictionary(info).unwrap();
+        let creation_date = info.get(b"CreationDate").unwrap().as_str().unwrap();
+        assert!(String::from_utf8_lossy(creation_date).starts_with("D:2001"));
+    }
+
+    #[test]
+    #[cfg(feature = "font_subsetting")]
+    fn test_font_subsetting() {
+        let path = "./tests/files/liberation/LiberationSans-Regular.ttf";
+        let font_size = std::fs::metadata(path).expect("Failed to read font").len() as usize;
+
+        // The heading adds a bookmark, so the document is re-saved with lopdf when it is written.
+        for with_heading in [false, true] {
+            let font_family =
+                super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                    .expect("Failed to load the default font family");
+            let mut doc = super::Document::new(font_family);
+            doc.set_two_pass_rendering(true);
+            if with_heading {
+                doc.push(super::elements::Heading::new("Heading"));
+            }
+            doc.push(super::elements::Paragraph::new("Subset"));
+            let mut data = Vec::new();
+            doc.render(&mut data).expect("Failed to render document");
+
+            let pdf = lopdf::Document::load_mem(&data).expect("Failed to load rendered document");
+            let font_files: Vec<_> = pdf
+                .objects
+                .values()
+                .filter_map(|object| object.as_dict().ok())
+                .filter_map(|dict| dict.get(b"FontFile2").ok())
+                .map(|font_file| {
+                    let id = font_file
+                        .as_reference()
+                        .expect("Invalid font file reference");
+                    let stream = pdf
+                        .get_object(id)
+                        .and_then(lopdf::Object::as_stream)
+                        .expect("Missing font file stream");
+                    stream
+                        .decompressed_content()
+                        .unwrap_or_else(|_| stream.content.clone())
+                })
+                .collect();
+            assert_eq!(1, font_files.len());
+            assert!(!font_files[0].is_empty());
+            assert!(
+                font_files[0].len() * 10 < font_size,
+                "Font file with {} bytes has not been subset",
+                font_files[0].len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_fallback_font_family() {
+        let load_font_family = |builtin| {
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", builtin)
+                .expect("Failed to load the font family")
+        };
+
+        let helvetica = Some(super::fonts::Builtin::Helvetica);
+        let mut doc = super::Document::new(load_font_family(helvetica));
+        doc.push(super::elements::Paragraph::new("Łódź"));
+        assert!(doc.render(std::io::sink()).is_err());
+
+        let mut doc = super::Document::new(load_font_family(helvetica));
+        let fallback = doc.add_fallback_font_family(load_font_family(None));
+        let style = super::style::Style::new();
+        let font_cache = doc.font_cache();
+        let default_font = font_cache.default_font_family().regular;
+        assert_eq!(default_font, style.char_font(font_cache, 'ó'));
+        assert_eq!(fallback.regular, style.char_font(font_cache, 'Ł'));
+        let runs: Vec<_> = style
+            .font_runs(font_cache, "Łódź")
+            .into_iter()
+            .map(|(_, s)| s)
+            .collect();
+        assert_eq!(vec!["Ł", "ód", "ź"], runs);
+
+        doc.push(super::elements::Paragraph::new("Łódź"));
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load rendered document");
+        let mut fonts: Vec<_> = pdf
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .filter(|dict| dict.has(b"BaseFont"))
+            .filter_map(|dict| dict.get(b"Subtype").and_then(lopdf::Object::as_name).ok())
+            .collect();
+        fonts.sort();
+        assert_eq!(vec![b"Type0".as_ref(), b"Type1"], fonts);
+    }
+
+    #[cfg(feature = "shaping")]
+    #[test]
+    fn test_shaping() {
+        let font_family =
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                .expect("Failed to load the default font family");
+        let doc = super::Document::new(font_family);
+        let font_cache = doc.font_cache();
+        let style = super::style::Style::new();
+        let font = style.font(font_cache);
+
+        // The combining accent is composed with the base character
+        let glyphs = font
+            .shape(font_cache, "a\u{301}", super::Direction::Auto)
+            .expect("Failed to shape text");
+        assert_eq!(1, glyphs.len());
+        assert_eq!(
+            style.str_width(font_cache, "á"),
+            style.str_width(font_cache, "a\u{301}")
+        );
+
+        // Right-to-left text is returned in visual order
+        let glyphs = font
+            .shape(font_cache, "שלום", super::Direction::Auto)
+            .expect("Failed to shape text");
+        let clusters: Vec<_> = glyphs.iter().map(|glyph| glyph.cluster).collect();
+        assert_eq!(vec![6, 4, 2, 0], clusters);
+
+        let builtin = super::fonts::from_files(
+            "./tests/files/liberation",
+            "LiberationSans",
+            Some(super::fonts::Builtin::Helvetica),
+        )
+        .expect("Failed to load the default font family");
+        let doc = super::Document::new(builtin);
+        let font_cache = doc.font_cache();
+        let font = style.font(font_cache);
+        assert_eq!(None, font.shape(font_cache, "abc", super::Direction::Auto));
+    }
+
+    #[test]
+    fn test_footer() {
+        use super::{Element as _, PageDecorator as _};
+        use float_cmp::approx_eq;
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        struct AreaHeight(super::elements::Paragraph, Rc<Cell<super::Mm>>);
+
+        impl super::Element for AreaHeight {
+            fn render(
+                &mut self,
+                context: &super::Context,
+                area: super::render::Area<'_>,
+                style: super::style::Style,
+            ) -> Result<super::RenderResult, super::error::Error> {
+                self.1.set(area.size().height);
+                self.0.render(context, area, style)
+            }
+
+            fn reset(&mut self) {
+                self.0.reset();
+            }
+        }
+
+        let font_family =
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                .expect("Failed to load the default font family");
+        let doc = super::Document::new(font_family);
+        let style = super::style::Style::new();
+        let area = super::render::Area::measuring(super::Size::new(80, 80));
+        let footer_height = super::elements::Paragraph::new("footer")
+            .measure(doc.context(), area, style)
+            .expect("Failed to measure the footer")
+            .size
+            .height;
+
+        let area_height = Rc::new(Cell::new(super::Mm(0.0)));
+        let mut decorator = super::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        let footer_area_height = area_height.clone();
+        decorator.set_footer(move |_| {
+            AreaHeight(
+                super::elements::Paragraph::new("footer"),
+                footer_area_height.clone(),
+            )
+        });
+        let page_area = super::render::Area::measuring(super::Size::new(100, 100));
+        let page_context = super::PageContext::new(super::PageInfo::new(1, None), (100, 100));
+        let area = decorator
+            .decorate_page(doc.context(), page_area, &page_context)
+            .expect("Failed to decorate the page");
+
+        // The footer is printed at the bottom of the page and the content ends above it.
+        let eq = |left, right| approx_eq!(super::Mm, left, right, epsilon = 0.001);
+        assert!(eq(footer_height, area_height.get()));
+        assert!(eq(super::Mm(80.0) - footer_height, area.size().height));
+        assert!(eq(super::Mm(80.0), area.size().width));
+    }
+
+    #[test]
+    fn test_page_kinds() {
+        use super::{Element as _, PageDecorator as _, PageKind};
+        use float_cmp::approx_eq;
+
+        let font_family =
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                .expect("Failed to load the default font family");
+        let doc = super::Document::new(font_family);
+        let style = super::style::Style::new();
+        let area = super::render::Area::measuring(super::Size::new(80, 80));
+        let header_height = super::elements::Paragraph::new("header")
+            .measure(doc.context(), area, style)
+            .expect("Failed to measure the header")
+            .size
+            .height;
+
+        let margins = super::Margins::trbl(10, 10, 10, 20);
+        assert_eq!(super::Margins::trbl(10, 20, 10, 10), margins.mirrored());
+
+        let mut decorator = super::SimplePageDecorator::new();
+        decorator.set_margins(margins);
+        decorator.set_page_margins(PageKind::Even, super::Margins::all(5));
+        decorator.set_header(|_| super::elements::Paragraph::new("header"));
+        decorator.hide_page_header(PageKind::First);
+        let mut decorate = |number| {
+            let page_context =
+                super::PageContext::new(super::PageInfo::new(number, None), (100, 100));
+            decorator
+                .decorate_page(
+                    doc.context(),
+                    super::render::Area::measuring(super::Size::new(100, 100)),
+                    &page_context,
+                )
+                .expect("Failed to decorate the page")
+                .size()
+        };
+        let eq = |left, right| approx_eq!(super::Size, left, right, epsilon = 0.001);
+
+        // The first page has no header, the even pages have different margins.
+        assert!(eq(super::Size::new(70, 80), decorate(1)));
+        assert!(eq(
+            super::Size::new(90, super::Mm(90.0) - header_height),
+            decorate(2)
+        ));
+        assert!(eq(
+            super::Size::new(70, super::Mm(80.0) - header_height),
+            decorate(3)
+        ));
+    }
+
+    #[test]
+    fn test_page_sizes() {
+        use super::Element as _;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct PaperSizes(Rc<RefCell<Vec<super::Size>>>);
+
+        impl super::PageDecorator for PaperSizes {
+            fn decorate_page<'a>(
+                &mut self,
+                _context: &super::Context,
+                area: super::render::Area<'a>,
+                page_context: &super::PageContext<'_>,
+            ) -> Result<super::render::Area<'a>, super::error::Error> {
+                self.0.borrow_mut().push(page_context.paper_size);
+                Ok(area)
+            }
+        }
+
+        let font_family =
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                .expect("Failed to load the default font family");
+        let mut doc = super::Document::new(font_family);
+        let paper_sizes = Rc::new(RefCell::new(Vec::new()));
+        doc.set_page_decorator(PaperSizes(paper_sizes.clone()));
+        doc.set_two_pass_rendering(true);
+        let portrait = super::Size::from(super::PaperSize::A4);
+        let landscape = portrait.landscape();
+        assert_eq!(super::Size::new(297, 210), landscape);
+        assert_eq!(portrait, landscape.portrait());
+
+        // Measuring a page break does not change the size of the next page.
+        let mut page_break = super::elements::PageBreak::new().with_size(landscape);
+        let area = super::render::Area::measuring(portrait);
+        page_break
+            .measure(doc.context(), area, super::style::Style::new())
+            .expect("Failed to measure the page break");
+        assert_eq!(None, doc.context().next_page_size.get());
+
+        doc.push(super::elements::Paragraph::new("portrait"));
+        doc.push(super::elements::PageBreak::new().with_size(landscape));
+        doc.push(super::elements::Paragraph::new("landscape"));
+        doc.push(super::elements::PageBreak::new());
+        doc.push(super::elements::Paragraph::new("landscape"));
+        doc.push(super::elements::PageBreak::new().with_size(portrait));
+        doc.push(super::elements::Paragraph::new("portrait"));
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+
+        let expected = vec![portrait, landscape, landscape, portrait];
+        let paper_sizes = paper_sizes.borrow();
+        assert_eq!(expected, paper_sizes[..4]);
+        assert_eq!(expected, paper_sizes[4..]);
+
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load rendered document");
+        let is_landscape: Vec<_> = pdf
+            .get_pages()
+            .values()
+            .map(|id| {
+                let page = pdf.get_dictionary(*id).expect("Failed to load page");
+                let media_box = page
+                    .get(b"MediaBox")
+                    .and_then(lopdf::Object::as_array)
+                    .expect("Failed to load media box");
+                let width = media_box[2].as_float().expect("Invalid media box");
+                let height = media_box[3].as_float().expect("Invalid media box");
+                width > height
+            })
+            .collect();
+        assert_eq!(vec![false, true, true, false], is_landscape);
+    }
+
+    #[test]
+    fn test_overlays() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Recorder(Rc<RefCell<Vec<(usize, super::Size)>>>);
+
+        impl super::Element for Recorder {
+            fn render(
+                &mut self,
+                context: &super::Context,
+                area: super::render::Area<'_>,
+                _style: super::style::Style,
+            ) -> Result<super::RenderResult, super::error::Error> {
+                self.0
+                    .borrow_mut()
+                    .push((context.page().number, area.size()));
+                Ok(super::RenderResult::default())
+            }
+        }
+
+        let selector = super::PageSelector::from(super::PageKind::Odd);
+        assert!(selector.matches(super::PageInfo::new(3, None)));
+        assert!(!selector.matches(super::PageInfo::new(2, None)));
+        let selector = super::PageSelector::Pages(vec![2]);
+        assert!(selector.matches(super::PageInfo::new(2, None)));
+        assert!(!selector.matches(super::PageInfo::new(1, None)));
+
+        let font_family =
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                .expect("Failed to load the default font family");
+        let mut doc = super::Document::new(font_family);
+        doc.set_two_pass_rendering(true);
+        let mut decorator = super::SimplePageDecorator::new();
+        decorator.set_margins(20);
+        decorator.set_footer(|_| super::elements::Paragraph::new("footer"));
+        doc.set_page_decorator(decorator);
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let stamp = super::PositionedElement::new(
+            Recorder(calls.clone()),
+            super::Anchor::PageCorner(super::Corner::BottomRight),
+            (50, 20),
+        )
+        .with_offset((10, 5));
+        doc.push_overlay(super::PageKind::Odd, stamp);
+        let background = super::PositionedElement::new(
+            super::elements::Paragraph::new("background"),
+            super::Anchor::ContentBox(super::Corner::TopLeft),
+            (30, 10),
+        )
+        .with_z_order(-1);
+        doc.push_overlay(super::PageSelector::Pages(vec![2]), background);
+
+        doc.push(super::elements::Paragraph::new("first"));
+        doc.push(super::elements::PageBreak::new());
+        doc.push(super::elements::Paragraph::new("second"));
+        doc.push(super::elements::PageBreak::new());
+        doc.push(super::elements::Paragraph::new("third"));
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+
+        let size = super::Size::new(50, 20);
+        assert_eq!(vec![(1, size), (3, size)], *calls.borrow());
+
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load rendered document");
+        let pages = pdf.get_pages();
+        let contents: Vec<_> = pages
+            .values()
+            .map(|id| {
+                pdf.get_and_decode_page_content(*id)
+                    .expect("Failed to load page")
+            })
+            .collect();
+        let clips: Vec<Vec<f32>> = contents
+            .iter()
+            .map(|content| {
+                content
+                    .operations
+                    .iter()
+                    .filter(|op| op.operator == "re")
+                    .flat_map(|op| op.operands.iter())
+                    .map(|operand| operand.as_float().expect("Invalid rectangle"))
+                    .collect()
+            })
+            .collect();
+        // The stamp is drawn above the footer and the background behind it.
+        let is_above: Vec<bool> = contents
+            .iter()
+            .map(|content| {
+                let position = |operator| {
+                    content
+                        .operations
+                        .iter()
+                        .position(|op| op.operator == operator)
+                };
+                position("re") > position("BT")
+            })
+            .collect();
+        assert_eq!(vec![true, false, true], is_above);
+        let rect = |x: f32, y: f32, width: f32, height: f32| -> Vec<f32> {
+            [x, y, width, height]
+                .iter()
+                .map(|mm| printpdf::Pt::from(printpdf::Mm(*mm)).0)
+                .collect()
+        };
+        let stamp = rect(150.0, 5.0, 50.0, 20.0);
+        let expected = [stamp.clone(), rect(20.0, 267.0, 30.0, 10.0), stamp];
+        assert_eq!(expected.len(), clips.len());
+        for (expected, actual) in expected.iter().zip(&clips) {
+            assert_eq!(expected.len(), actual.len());
+            for (expected, actual) in expected.iter().zip(actual) {
+                assert!(
+                    (expected - actual).abs() < 0.01,
+                    "{} != {}",
+                    expected,
+                    actual
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_watermark() {
+        let font_family =
+            super::fonts::from_files("./tests/files/liberation", "LiberationSans", None)
+                .expect("Failed to load the default font family");
+        let mut doc = super::Document::new(font_family);
+        let watermark = super::elements::Watermark::text("DRAFT")
+            .with_rotation(-30.0)
+            .with_opacity(0.5)
+            .with_z_order(1);
+        doc.push_watermark(super::PageSelector::Pages(vec![2]), watermark);
+        doc.push(super::elements::Paragraph::new("first"));
+        doc.push(super::elements::PageBreak::new());
+        doc.push(super::elements::Paragraph::new("second"));
+        let mut data = Vec::new();
+        doc.render(&mut data).expect("Failed to render document");
+
+        let pdf = lopdf::Document::load_mem(&data).expect("Failed to load rendered document");
+        let pages: Vec<_> = pdf.get_pages().values().copied().collect();
+        assert_eq!(2, pages.len());
+        let opacities: Vec<Option<f32>> = pages
+            .iter()
+            .map(|id| {
+                let resources = pdf
+                    .get_dictionary(*id)
+                    .and_then(|page| page.get(b"Resources"))
+                    .and_then(lopdf::Object::as_reference)
+                    .and_then(|id| pdf.get_dictionary(id))
+                    .expect("Failed to load page resources");
+                resources
+                    .get(b"ExtGState")
+                    .and_then(lopdf::Object::as_dict)
+                    .and_then(|states| states.get(b"GenpdfOpacity500"))
+                    .and_then(lopdf::Object::as_dict)
+                    .and_then(|state| state.get(b"ca"))
+                    .and_then(lopdf::Object::as_float)
+                    .ok()
+            })
+            .collect();
+        assert_eq!(vec![None, Some(0.5)], opacities);
+
+        let transforms: Vec<Vec<f32>> = pages
+            .iter()
+            .map(|id| {
+                let content = pdf
+                    .get_and_decode_page_content(*id)
+                    .expect("Failed to load page");
+                content
+                    .operations
+                    .iter()
+                    .filter(|op| op.operator == "cm")
+                    .flat_map(|op| op.operands.iter())
+                    .map(|operand| operand.as_float().expect("Invalid transformation"))
+                    .collect()
+            })
+            .collect();
+        assert!(transforms[0].is_empty());
+        assert_eq!(6, transforms[1].len());
+        // The text is rotated counter-clockwise by 30 degrees in user space
+        let angle = transforms[1][1].atan2(transforms[1][0]).to_degrees();
+        assert!((angle - 30.0).abs() < 0.01, "{} != 30", angle);
+    }
+
     #[test]
     fn test_rotation() {
         use super::Rotation;
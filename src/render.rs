@@ -8,25 +8,35 @@
 //! lines and text.  For more advanced text formatting, you can create a [`TextSection`][] from an
 //! [`Area`][].
 //!
+//! Text that is printed with a [`LinkId`][] in its style is turned into a clickable link
+//! annotation, see [`Renderer::add_link`][].  Links to named destinations (see [`Area::add_destination`][]) are resolved when the document
+//! is written.  Similarly, the bookmarks added with [`Area::add_bookmark`][] are combined into the
+//! outline of the document when it is written.
+//!
 //! [`printpdf`]: https://docs.rs/printpdf/latest/printpdf
 //! [`Renderer`]: struct.Renderer.html
 //! [`Page`]: struct.Page.html
 //! [`Layer`]: struct.Layer.html
 //! [`Area`]: struct.Area.html
 //! [`TextSection`]: struct.TextSection.html
+//! [`LinkId`]: ../style/struct.LinkId.html
+//! [`Renderer::add_link`]: struct.Renderer.html#method.add_link
+//! [`Area::add_destination`]: struct.Area.html#method.add_destination
+//! [`Area::add_bookmark`]: struct.Area.html#method.add_bookmark
 
 use std::cell;
+use std::collections;
 use std::io;
 use std::ops;
 use std::rc;
 
 use crate::error::{Context as _, Error, ErrorKind};
 use crate::fonts;
-use crate::style::{Color, LineStyle, Style};
-use crate::{Margins, Mm, Position, Size};
+use crate::style::{Color, LineStyle, Link, LinkId, LinkRegistry, Style};
+use crate::{Direction, Margins, Mm, Position, Rotation, Size};
 
 #[cfg(feature = "images")]
-use crate::{Rotation, Scale};
+use crate::Scale;
 
 /// A position relative to the top left corner of a layer.
 struct LayerPosition(Position);
@@ -69,9 +79,17 @@ impl ops::Deref for UserSpacePosition {
 ///
 /// [`printpdf::PdfDocumentReference`]: https://docs.rs/printpdf/0.3.2/printpdf/types/pdf_document/struct.PdfDocumentReference.html
 pub struct Renderer {
-    doc: printpdf::PdfDocumentReference,
+    // The document is only shared using weak references (see FontCache::load_pdf_fonts), so we can
+    // always take it out of the Rc.
+    doc: rc::Rc<printpdf::PdfDocumentReference>,
     // invariant: pages.len() >= 1
     pages: Vec<Page>,
+    // The link targets, shared with the pages
+    links: rc::Rc<cell::RefCell<LinkRegistry>>,
+    // The document settings are applied in write so that the document is never replaced
+    conformance: Option<printpdf::PdfConformance>,
+    creation_date: Option<printpdf::OffsetDateTime>,
+    modification_date: Option<printpdf::OffsetDateTime>,
 }
 
 impl Renderer {
@@ -86,29 +104,34 @@ impl Renderer {
         );
         let page_ref = doc.get_page(page_idx);
         let layer_ref = page_ref.get_layer(layer_idx);
-        let page = Page::new(page_ref, layer_ref, size);
+        let links = rc::Rc::new(cell::RefCell::new(LinkRegistry::new()));
+        let page = Page::new(page_ref, layer_ref, size, links.clone());
 
         Ok(Renderer {
-            doc,
+            doc: rc::Rc::new(doc),
             pages: vec![page],
+            links,
+            conformance: None,
+            creation_date: None,
+            modification_date: None,
         })
     }
 
     /// Sets the PDF conformance for the generated PDF document.
     pub fn with_conformance(mut self, conformance: printpdf::PdfConformance) -> Self {
-        self.doc = self.doc.with_conformance(conformance);
+        self.conformance = Some(conformance);
         self
     }
 
     /// Sets the creation date for the generated PDF document.
     pub fn with_creation_date(mut self, date: printpdf::OffsetDateTime) -> Self {
-        self.doc = self.doc.with_creation_date(date);
+        self.creation_date = Some(date);
         self
     }
 
     /// Sets the modification date for the generated PDF document.
     pub fn with_modification_date(mut self, date: printpdf::OffsetDateTime) -> Self {
-        self.doc = self.doc.with_mod_date(date);
+        self.modification_date = Some(date);
         self
     }
 
@@ -120,7 +143,22 @@ impl Renderer {
                 .add_page(size.width.into(), size.height.into(), "Layer 1");
         let page_ref = self.doc.get_page(page_idx);
         let layer_ref = page_ref.get_layer(layer_idx);
-        self.pages.push(Page::new(page_ref, layer_ref, size))
+        self.pages
+            .push(Page::new(page_ref, layer_ref, size, self.links.clone()))
+    }
+
+    /// Registers the given link target for this document and returns a reference to it that can
+    /// be set in the [`Style`][] of the printed text.
+    ///
+    /// [`Style`]: ../style/struct.Style.html
+    pub fn add_link(&mut self, link: Link) -> LinkId {
+        self.links.borrow_mut().add(link)
+    }
+
+    /// Replaces the registered link targets with the given registry, so that the link references
+    /// returned by the registry can be used for this document.
+    pub(crate) fn set_links(&mut self, links: LinkRegistry) {
+        *self.links.borrow_mut() = links;
     }
 
     /// Returns the number of pages in this document.
@@ -172,18 +210,363 @@ impl Renderer {
 
     /// Loads the font from the given data, adds it to the generated document and returns a
     /// reference to it.
+    ///
+    /// If the `font_subsetting` feature is enabled, only the glyphs that are used in the document
+    /// are embedded when the document is written, and the font is dropped if it is not used at all.
+    /// Note that the [`FontCache`][] only adds fonts to the document once they are used.
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn add_embedded_font(&self, data: &[u8]) -> Result<printpdf::IndirectFontRef, Error> {
         self.doc
             .add_external_font(data)
             .context("Failed to load PDF font")
     }
 
+    /// Returns a weak reference to the generated document that can be used to add fonts to it
+    /// while the document is rendered.
+    pub(crate) fn downgrade_doc(&self) -> rc::Weak<printpdf::PdfDocumentReference> {
+        rc::Rc::downgrade(&self.doc)
+    }
+
     /// Writes this PDF document to a writer.
+    ///
+    /// If the document contains named destinations, links to named destinations, bookmarks or
+    /// opacities, they are added to the document after it has been generated by `printpdf`.  Links
+    /// to destinations that do not exist cause an error.
     pub fn write(self, w: impl io::Write) -> Result<(), Error> {
-        self.doc
-            .save(&mut io::BufWriter::new(w))
+        let mut destinations = Vec::new();
+        let mut bookmarks = Vec::new();
+        let mut opacities = Vec::new();
+        let mut has_internal_links = false;
+        for (idx, page) in self.pages.iter().enumerate() {
+            for (name, top) in page.destinations.take() {
+                destinations.push((name, idx, top));
+            }
+            for (title, level, top) in page.bookmarks.take() {
+                bookmarks.push((title, level, idx, top));
+            }
+            for permille in page.opacities.take() {
+                opacities.push((idx, permille));
+            }
+            has_internal_links |= page.has_internal_links.get();
+        }
+
+        if destinations.is_empty()
+            && bookmarks.is_empty()
+            && opacities.is_empty()
+            && !has_internal_links
+        {
+            return self
+                .into_doc()
+                .save(&mut io::BufWriter::new(w))
+                .context("Failed to save document");
+        }
+
+        let mut data = Vec::new();
+        self.into_doc()
+            .save(&mut io::BufWriter::new(&mut data))
+            .context("Failed to save document")?;
+        let mut doc = lopdf::Document::load_mem(&data).map_err(|err| {
+            Error::new(
+                format!("Failed to load generated document: {}", err),
+                ErrorKind::Internal,
+            )
+        })?;
+        add_destinations(&mut doc, destinations)?;
+        add_outline(&mut doc, bookmarks)?;
+        add_opacities(&mut doc, opacities)?;
+        doc.save_to(&mut io::BufWriter::new(w))
             .context("Failed to save document")
     }
+
+    /// Takes the PDF document out of this renderer and applies the document settings.
+    fn into_doc(self) -> printpdf::PdfDocumentReference {
+        let mut doc = unwrap_doc(self.doc);
+        if let Some(conformance) = self.conformance {
+            doc = doc.with_conformance(conformance);
+        }
+        if let Some(date) = self.creation_date {
+            doc = doc.with_creation_date(date);
+        }
+        if let Some(date) = self.modification_date {
+            doc = doc.with_mod_date(date);
+        }
+        doc
+    }
+}
+
+/// Takes the PDF document out of the given reference.
+fn unwrap_doc(doc: rc::Rc<printpdf::PdfDocumentReference>) -> printpdf::PdfDocumentReference {
+    rc::Rc::try_unwrap(doc)
+        .unwrap_or_else(|_| panic!("Invariant violated: PDF document is still referenced"))
+}
+
+/// Adds the given named destinations to the document and resolves the links to them.
+///
+/// `printpdf` only supports URI actions for link annotations, so links to named destinations are
+/// written as actions with the type `GoTo` and the destination name stored as the URI.  These
+/// actions are replaced with proper `GoTo` actions here.
+fn add_destinations(
+    doc: &mut lopdf::Document,
+    destinations: Vec<(String, usize, Mm)>,
+) -> Result<(), Error> {
+    use lopdf::Object;
+
+    let pages = doc.get_pages();
+    let mut names = collections::BTreeMap::new();
+    for (name, idx, top) in destinations {
+        let destination = destination(&pages, idx, top)?;
+        // If a name is used for several destinations, the first one is used.
+        names.entry(name.into_bytes()).or_insert(destination);
+    }
+
+    for object in doc.objects.values_mut() {
+        let annotation = match object {
+            Object::Dictionary(dict) if dict.has(b"Subtype") => dict,
+            _ => continue,
+        };
+        if annotation.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Link") {
+            continue;
+        }
+        if let Ok(Object::Dictionary(action)) = annotation.get_mut(b"A") {
+            if action.get(b"S").and_then(Object::as_name).ok() != Some(b"GoTo") {
+                continue;
+            }
+            if let Some(Object::String(name, _)) = action.remove(b"URI") {
+                if !names.contains_key(&name) {
+                    return Err(Error::new(
+                        format!(
+                            "Link to unknown destination: {}",
+                            String::from_utf8_lossy(&name)
+                        ),
+                        ErrorKind::InvalidData,
+                    ));
+                }
+                action.set("D", Object::string_literal(name));
+            }
+        }
+    }
+
+    if !names.is_empty() {
+        let names: Vec<_> = names
+            .into_iter()
+            .flat_map(|(name, destination)| vec![Object::string_literal(name), destination])
+            .collect();
+        let dests_id = doc.add_object(lopdf::dictionary! { "Names" => names });
+        let catalog = doc.catalog_mut().map_err(|err| {
+            Error::new(
+                format!("Failed to access the document catalog: {}", err),
+                ErrorKind::Internal,
+            )
+        })?;
+        catalog.set("Names", lopdf::dictionary! { "Dests" => dests_id });
+    }
+    Ok(())
+}
+
+/// Returns the name of the extended graphics state for the given opacity in 1/1000.
+fn opacity_name(permille: u16) -> String {
+    format!("GenpdfOpacity{}", permille)
+}
+
+/// Adds extended graphics states with the given opacities in 1/1000 to the resources of the pages
+/// with the given indices, see [`Page::add_opacity`][].
+///
+/// [`Page::add_opacity`]: struct.Page.html#method.add_opacity
+fn add_opacities(doc: &mut lopdf::Document, opacities: Vec<(usize, u16)>) -> Result<(), Error> {
+    use lopdf::Object;
+
+    let internal_error = |err: lopdf::Error| {
+        Error::new(
+            format!("Failed to add the opacity to the page resources: {}", err),
+            ErrorKind::Internal,
+        )
+    };
+
+    let pages = doc.get_pages();
+    for (idx, permille) in opacities {
+        let page_id = pages.get(&(idx as u32 + 1)).copied().ok_or_else(|| {
+            Error::new(
+                format!("Missing page {} in the generated document", idx),
+                ErrorKind::Internal,
+            )
+        })?;
+        let resources_id = match doc
+            .get_dictionary(page_id)
+            .map_err(internal_error)?
+            .get(b"Resources")
+        {
+            Ok(Object::Reference(id)) => Some(*id),
+            _ => None,
+        };
+        let resources = match resources_id {
+            Some(id) => doc.get_dictionary_mut(id).map_err(internal_error)?,
+            None => {
+                let page = doc.get_dictionary_mut(page_id).map_err(internal_error)?;
+                if !page.has(b"Resources") {
+                    page.set("Resources", lopdf::Dictionary::new());
+                }
+                page.get_mut(b"Resources")
+                    .and_then(Object::as_dict_mut)
+                    .map_err(internal_error)?
+            }
+        };
+        if !resources.has(b"ExtGState") {
+            resources.set("ExtGState", lopdf::Dictionary::new());
+        }
+        let opacity = f32::from(permille) / 1000.0;
+        resources
+            .get_mut(b"ExtGState")
+            .and_then(Object::as_dict_mut)
+            .map_err(internal_error)?
+            .set(
+                opacity_name(permille),
+                lopdf::dictionary! {
+                    "Type" => "ExtGState",
+                    "ca" => opacity,
+                    "CA" => opacity
+                },
+            );
+    }
+    Ok(())
+}
+
+/// Replaces the outline of the document with a tree of the given bookmarks.
+///
+/// The bookmarks are nested according to their level:  A bookmark is added as a child of the
+/// last preceding bookmark with a lower level.
+fn add_outline(
+    doc: &mut lopdf::Document,
+    bookmarks: Vec<(String, usize, usize, Mm)>,
+) -> Result<(), Error> {
+    use lopdf::Object;
+
+    if bookmarks.is_empty() {
+        return Ok(());
+    }
+
+    let pages = doc.get_pages();
+    let root_id = doc.new_object_id();
+    let mut items: Vec<OutlineItem> = Vec::new();
+    let mut ids = Vec::new();
+    let mut top_level = Vec::new();
+    // The indizes and levels of the bookmarks that can have children
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for (title, level, idx, top) in bookmarks {
+        while stack.last().map(|(_, l)| *l >= level).unwrap_or_default() {
+            stack.pop();
+        }
+        let parent = stack.last().map(|(i, _)| *i);
+        let item = items.len();
+        ids.push(doc.new_object_id());
+        items.push(OutlineItem {
+            dict: lopdf::dictionary! {
+                "Title" => text_string(&title),
+                "Parent" => parent.map(|i| ids[i]).unwrap_or(root_id),
+                "Dest" => destination(&pages, idx, top)?
+            },
+            children: Vec::new(),
+        });
+        if let Some(parent) = parent {
+            items[parent].children.push(item);
+        } else {
+            top_level.push(item);
+        }
+        stack.push((item, level));
+    }
+
+    // Link the siblings and count the descendants, starting with the last bookmark so that the
+    // children are processed before their parents.
+    let mut counts = vec![0; items.len()];
+    for item in (0..items.len()).rev() {
+        let children = items[item].children.clone();
+        counts[item] = children.iter().map(|child| counts[*child] + 1).sum();
+        link_outline_items(&mut items, &ids, &children);
+        if let (Some(first), Some(last)) = (children.first(), children.last()) {
+            let dict = &mut items[item].dict;
+            dict.set("First", ids[*first]);
+            dict.set("Last", ids[*last]);
+            dict.set("Count", i64::try_from(counts[item]).unwrap_or(i64::MAX));
+        }
+    }
+    link_outline_items(&mut items, &ids, &top_level);
+    let count: usize = top_level.iter().map(|item| counts[*item] + 1).sum();
+    let root = lopdf::dictionary! {
+        "Type" => "Outlines",
+        "First" => ids[top_level[0]],
+        "Last" => ids[top_level[top_level.len() - 1]],
+        "Count" => i64::try_from(count).unwrap_or(i64::MAX)
+    };
+
+    for (item, id) in items.into_iter().zip(ids) {
+        doc.objects.insert(id, Object::Dictionary(item.dict));
+    }
+    doc.objects.insert(root_id, Object::Dictionary(root));
+
+    let catalog = doc.catalog_mut().map_err(|err| {
+        Error::new(
+            format!("Failed to access the document catalog: {}", err),
+            ErrorKind::Internal,
+        )
+    })?;
+    let old_outline = catalog.get(b"Outlines").and_then(Object::as_reference).ok();
+    catalog.set("Outlines", root_id);
+    catalog.set("PageMode", "UseOutlines");
+    if let Some(old_outline) = old_outline {
+        doc.objects.remove(&old_outline);
+    }
+    Ok(())
+}
+
+/// An item of the document outline that has not been added to the document yet.
+struct OutlineItem {
+    dict: lopdf::Dictionary,
+    children: Vec<usize>,
+}
+
+/// Sets the `Prev` and `Next` entries for the given sibling outline items.
+fn link_outline_items(items: &mut [OutlineItem], ids: &[lopdf::ObjectId], siblings: &[usize]) {
+    for pair in siblings.windows(2) {
+        items[pair[0]].dict.set("Next", ids[pair[1]]);
+        items[pair[1]].dict.set("Prev", ids[pair[0]]);
+    }
+}
+
+/// Returns an explicit destination for the given vertical position in user space on the page with
+/// the given index.
+fn destination(
+    pages: &collections::BTreeMap<u32, lopdf::ObjectId>,
+    idx: usize,
+    top: Mm,
+) -> Result<lopdf::Object, Error> {
+    use lopdf::Object;
+
+    let page_id = u32::try_from(idx + 1)
+        .ok()
+        .and_then(|number| pages.get(&number))
+        .ok_or_else(|| Error::new("Missing page for destination", ErrorKind::Internal))?;
+    let top: printpdf::Pt = printpdf::Mm::from(top).into();
+    Ok(Object::Array(vec![
+        Object::Reference(*page_id),
+        Object::Name(b"XYZ".to_vec()),
+        Object::Null,
+        Object::Real(top.0),
+        Object::Null,
+    ]))
+}
+
+/// Encodes the given string as a PDF text string, using UTF-16 if it contains non-ASCII
+/// characters.
+fn text_string(s: &str) -> lopdf::Object {
+    if s.is_ascii() {
+        lopdf::Object::string_literal(s)
+    } else {
+        let mut bytes = vec![0xfe, 0xff];
+        for c in s.encode_utf16() {
+            bytes.extend_from_slice(&c.to_be_bytes());
+        }
+        lopdf::Object::String(bytes, lopdf::StringFormat::Hexadecimal)
+    }
 }
 
 /// A page of a PDF document.
@@ -195,6 +578,11 @@ pub struct Page {
     page: printpdf::PdfPageReference,
     size: Size,
     layers: Layers,
+    destinations: cell::RefCell<Vec<(String, Mm)>>,
+    bookmarks: cell::RefCell<Vec<(String, usize, Mm)>>,
+    has_internal_links: cell::Cell<bool>,
+    opacities: cell::RefCell<collections::BTreeSet<u16>>,
+    links: rc::Rc<cell::RefCell<LinkRegistry>>,
 }
 
 impl Page {
@@ -202,11 +590,17 @@ impl Page {
         page: printpdf::PdfPageReference,
         layer: printpdf::PdfLayerReference,
         size: Size,
+        links: rc::Rc<cell::RefCell<LinkRegistry>>,
     ) -> Page {
         Page {
             page,
             size,
             layers: Layers::new(layer),
+            destinations: Default::default(),
+            bookmarks: Default::default(),
+            has_internal_links: Default::default(),
+            opacities: Default::default(),
+            links,
         }
     }
 
@@ -236,6 +630,31 @@ impl Page {
         Layer::new(self, self.layers.last())
     }
 
+    /// Adds a named destination with the given vertical position in user space to this page.
+    fn add_destination(&self, name: &str, top: Mm) {
+        self.destinations.borrow_mut().push((name.to_owned(), top));
+    }
+
+    /// Adds a bookmark with the given vertical position in user space to this page.
+    fn add_bookmark(&self, title: &str, level: usize, top: Mm) {
+        self.bookmarks
+            .borrow_mut()
+            .push((title.to_owned(), level, top));
+    }
+
+    /// Registers the given opacity for this page and returns the name of the extended graphics
+    /// state that sets it.
+    ///
+    /// `printpdf` cannot create graphics states with alpha constants, so they are added to the
+    /// page resources when the document is written, see [`Renderer::write`][].
+    ///
+    /// [`Renderer::write`]: struct.Renderer.html#method.write
+    fn add_opacity(&self, opacity: f32) -> String {
+        let permille = (opacity * 1000.0).round() as u16;
+        self.opacities.borrow_mut().insert(permille);
+        opacity_name(permille)
+    }
+
     fn next_layer(&self, layer: &printpdf::PdfLayerReference) -> Layer<'_> {
         let layer = self.layers.next(layer).unwrap_or_else(|| {
             let layer = self
@@ -384,6 +803,69 @@ impl<'p> Layer<'p> {
         self.data.layer.restore_graphics_state();
     }
 
+    /// Saves the graphics state so that the following changes to it are reverted when
+    /// [`restore_state`][] is called.
+    ///
+    /// [`restore_state`]: #method.restore_state
+    fn save_state(&self) -> LayerState {
+        self.data.layer.save_graphics_state();
+        self.data.state()
+    }
+
+    /// Restores the graphics state that was saved by [`save_state`][].
+    ///
+    /// [`save_state`]: #method.save_state
+    fn restore_state(&self, state: LayerState) {
+        self.data.layer.restore_graphics_state();
+        self.data.set_state(state);
+    }
+
+    /// Restricts drawing to the rectangle between the given upper left and lower right corners.
+    fn add_clip(&self, top_left: LayerPosition, bottom_right: LayerPosition) {
+        let top_left = self.transform_position(top_left);
+        let bottom_right = self.transform_position(bottom_right);
+        let rect = printpdf::Rect::new(
+            top_left.x.into(),
+            bottom_right.y.into(),
+            bottom_right.x.into(),
+            top_left.y.into(),
+        )
+        .with_mode(printpdf::path::PaintMode::Clip);
+        self.data.layer.add_rect(rect);
+    }
+
+    /// Sets the opacity for filling and stroking, see [`Page::add_opacity`][].
+    ///
+    /// [`Page::add_opacity`]: struct.Page.html#method.add_opacity
+    fn set_opacity(&self, opacity: f32) {
+        let name = self.page.add_opacity(opacity);
+        self.data
+            .layer
+            .add_operation(printpdf::lopdf::content::Operation::new(
+                "gs",
+                vec![printpdf::lopdf::Object::Name(name.into_bytes())],
+            ));
+    }
+
+    /// Rotates clockwise by the given rotation and scales by the given factor around the given
+    /// center.
+    fn add_transform(&self, center: LayerPosition, rotation: Rotation, scale: f32) {
+        let center = self.transform_position(center);
+        let x = printpdf::Pt::from(printpdf::Mm::from(center.x)).0;
+        let y = printpdf::Pt::from(printpdf::Mm::from(center.y)).0;
+        // The user space is rotated counter-clockwise
+        let (sin, cos) = (-rotation.degrees).to_radians().sin_cos();
+        let (a, b, c, d) = (cos * scale, sin * scale, -sin * scale, cos * scale);
+        self.data.layer.set_ctm(printpdf::CurTransMat::Raw([
+            a,
+            b,
+            c,
+            d,
+            x - a * x - c * y,
+            y - b * x - d * y,
+        ]));
+    }
+
     fn set_fill_color(&self, color: Option<Color>) {
         if self.data.update_fill_color(color) {
             self.data
@@ -448,6 +930,11 @@ impl<'p> Layer<'p> {
         self.data.layer.set_line_height(line_height.0);
     }
 
+    #[cfg(feature = "shaping")]
+    fn set_text_rise(&self, rise: Mm) {
+        self.data.layer.set_line_offset(printpdf::Pt::from(rise).0);
+    }
+
     fn set_font(&self, font: &printpdf::IndirectFontRef, font_size: u8) {
         self.data.layer.set_font(font, font_size.into());
     }
@@ -462,6 +949,46 @@ impl<'p> Layer<'p> {
             .write_positioned_codepoints(positions.into_iter().zip(codepoints.into_iter()));
     }
 
+    fn add_link(&self, position: LayerPosition, size: Size, link: &Link) {
+        let top_left = self.transform_position(position);
+        let rect = printpdf::Rect::new(
+            top_left.x.into(),
+            (top_left.y - size.height).into(),
+            (top_left.x + size.width).into(),
+            top_left.y.into(),
+        );
+        let action = match link {
+            Link::Uri(uri) => printpdf::Actions::uri(uri.clone()),
+            Link::Destination(name) => {
+                // Resolved in Renderer::write
+                self.page.has_internal_links.set(true);
+                printpdf::Actions {
+                    s: "GoTo".to_owned(),
+                    uri: name.clone(),
+                }
+            }
+        };
+        self.data
+            .layer
+            .add_link_annotation(printpdf::LinkAnnotation::new(
+                rect,
+                Some(printpdf::BorderArray::Solid([0.0, 0.0, 0.0])),
+                Some(printpdf::ColorArray::Transparent),
+                action,
+                None,
+            ));
+    }
+
+    fn add_destination(&self, name: &str, position: LayerPosition) {
+        let position = self.transform_position(position);
+        self.page.add_destination(name, position.y);
+    }
+
+    fn add_bookmark(&self, title: &str, level: usize, position: LayerPosition) {
+        let position = self.transform_position(position);
+        self.page.add_bookmark(title, level, position.y);
+    }
+
     /// Transforms the given position that is relative to the upper left corner of the layer to a
     /// position that is relative to the lower left corner of the layer (as used by `printpdf`).
     fn transform_position(&self, position: LayerPosition) -> UserSpacePosition {
@@ -495,6 +1022,31 @@ impl LayerData {
         self.outline_thickness.replace(thickness) != thickness
     }
     
+    pub fn state(&self) -> LayerState {
+        LayerState {
+            fill_color: self.fill_color.get(),
+            outline_color: self.outline_color.get(),
+            outline_thickness: self.outline_thickness.get(),
+            outline_dash: [
+                self.outline_dash.get(),
+                self.outline_gap.get(),
+                self.outline_dash2.get(),
+                self.outline_gap2.get(),
+            ],
+        }
+    }
+
+    pub fn set_state(&self, state: LayerState) {
+        self.fill_color.set(state.fill_color);
+        self.outline_color.set(state.outline_color);
+        self.outline_thickness.set(state.outline_thickness);
+        let [dash, gap, dash2, gap2] = state.outline_dash;
+        self.outline_dash.set(dash);
+        self.outline_gap.set(gap);
+        self.outline_dash2.set(dash2);
+        self.outline_gap2.set(gap2);
+    }
+
     pub fn update_outline_dash(&self, dash: i64, gap: i64, dash2: i64, gap2:i64) -> bool {        
         let _ = self.outline_dash2.replace(Some(dash2)) != Some(dash2);
         let _ = self.outline_gap.replace(Some(gap)) != Some(gap);
@@ -503,6 +1055,17 @@ impl LayerData {
     }
 }
 
+/// A snapshot of the cached graphics state of a [`LayerData`][].
+///
+/// [`LayerData`]: struct.LayerData.html
+#[derive(Clone, Copy, Debug)]
+struct LayerState {
+    fill_color: Color,
+    outline_color: Color,
+    outline_thickness: Mm,
+    outline_dash: [Option<i64>; 4],
+}
+
 impl From<printpdf::PdfLayerReference> for LayerData {
     fn from(layer: printpdf::PdfLayerReference) -> Self {
         Self {
@@ -524,32 +1087,254 @@ impl From<printpdf::PdfLayerReference> for LayerData {
 /// is defined by the layer that is drawn on and the origin and the size of the area.
 ///
 /// [`printpdf::PdfLayerReference`]: https://docs.rs/printpdf/0.3.2/printpdf/types/pdf_layer/struct.PdfLayerReference.html
+///
+/// An area can also be created without a layer using [`Area::measuring`][].  Such an area only
+/// keeps track of its origin and size, and all drawing operations are ignored.  This can be used to
+/// lay out elements without adding them to the document, for example to measure their size.
+///
+/// [`Area::measuring`]: #method.measuring
+///
+/// An area keeps track of the rectangles that are reserved by floating elements (see
+/// [`Area::add_float`][]).  The reserved rectangles are shared with all copies of the area.
+///
+/// [`Area::add_float`]: #method.add_float
 #[derive(Clone)]
 pub struct Area<'p> {
-    layer: Layer<'p>,
+    layer: Option<Layer<'p>>,
     origin: Position,
     size: Size,
+    floats: rc::Rc<cell::RefCell<Vec<(Position, Size)>>>,
+    // Whether this measuring area is used to lay out a page of the document
+    is_page_layout: bool,
+    // The height of the content that has been rendered above this area on the current page
+    used_height: Mm,
 }
 
 impl<'p> Area<'p> {
     fn new(layer: Layer<'p>, origin: Position, size: Size) -> Area<'p> {
         Area {
-            layer,
+            layer: Some(layer),
             origin,
             size,
+            floats: Default::default(),
+            is_page_layout: false,
+            used_height: Mm(0.0),
         }
     }
 
+    /// Creates a new measuring area with the given size that is not backed by a PDF layer.
+    ///
+    /// Elements can be rendered into a measuring area as usual, but nothing is written to the
+    /// document.
+    pub fn measuring(size: impl Into<Size>) -> Area<'p> {
+        Area {
+            layer: None,
+            origin: Position::default(),
+            size: size.into(),
+            floats: Default::default(),
+            is_page_layout: false,
+            used_height: Mm(0.0),
+        }
+    }
+
+    /// Creates a new measuring area with the given size that is used to lay out a page of the
+    /// document without writing it, for example to count the pages of the document.
+    pub(crate) fn page_layout(size: impl Into<Size>) -> Area<'p> {
+        Area {
+            is_page_layout: true,
+            ..Area::measuring(size)
+        }
+    }
+
+    /// Returns `true` if this is a measuring area that is not backed by a PDF layer.
+    pub fn is_measuring(&self) -> bool {
+        self.layer.is_none()
+    }
+
+    /// Returns `true` if this area is used to lay out the pages of the document, i. e. if it is
+    /// backed by a PDF layer or has been created to lay out a page without writing it.  Copies of
+    /// this area created with [`to_measuring`][] are only used to measure elements.
+    ///
+    /// Elements should only change the following pages of the document, for example their paper
+    /// size, if this method returns `true`.
+    ///
+    /// [`to_measuring`]: #method.to_measuring
+    pub(crate) fn is_layout(&self) -> bool {
+        self.layer.is_some() || self.is_page_layout
+    }
+
     /// Returns a copy of this area on the next layer of the page.
     ///
     /// If this area is not on the last layer, the existing next layer is used.  If it is on the
-    /// last layer, a new layer is created and added to the page.
+    /// last layer, a new layer is created and added to the page.  For a measuring area, a copy of
+    /// this area is returned.
     pub fn next_layer(&self) -> Self {
-        let layer = self.layer.next();
+        let layer = self.layer.as_ref().map(Layer::next);
         Self {
             layer,
             origin: self.origin,
             size: self.size,
+            floats: self.floats.clone(),
+            is_page_layout: self.is_page_layout,
+            used_height: self.used_height,
+        }
+    }
+
+    /// Returns a copy of this area on the layer of the given area.
+    ///
+    /// If the given area is a measuring area, the copy is a measuring area too.
+    pub(crate) fn with_layer_of(&self, area: &Area<'p>) -> Self {
+        Self {
+            layer: area.layer.clone(),
+            origin: self.origin,
+            size: self.size,
+            floats: self.floats.clone(),
+            is_page_layout: area.is_page_layout,
+            used_height: self.used_height,
+        }
+    }
+
+    /// Returns a measuring copy of this area with the same origin and size.
+    ///
+    /// The measuring area has a copy of the floats of this area, so floats that are added to it
+    /// are not added to this area.
+    pub fn to_measuring(&self) -> Area<'p> {
+        Area {
+            layer: None,
+            origin: self.origin,
+            size: self.size,
+            floats: rc::Rc::new(cell::RefCell::new(self.floats.borrow().clone())),
+            is_page_layout: false,
+            used_height: self.used_height,
+        }
+    }
+
+    /// Reserves a rectangle with the given position (relative to this area) and size for a
+    /// floating element.
+    ///
+    /// The rectangle is shared with all copies of this area.  Text that is printed later in this
+    /// area or one of its copies should flow around it, see [`line_bounds`][].
+    ///
+    /// [`line_bounds`]: #method.line_bounds
+    pub fn add_float(&self, position: impl Into<Position>, size: impl Into<Size>) {
+        let position = position.into();
+        let origin = Position::new(self.origin.x + position.x, self.origin.y + position.y);
+        self.floats.borrow_mut().push((origin, size.into()));
+    }
+
+    /// Returns `true` if a rectangle reserved by a floating element overlaps this area, see
+    /// [`add_float`][].
+    ///
+    /// [`add_float`]: #method.add_float
+    pub fn has_floats(&self) -> bool {
+        self.floats.borrow().iter().any(|(origin, size)| {
+            origin.x < self.origin.x + self.size.width
+                && origin.x + size.width > self.origin.x
+                && origin.y < self.origin.y + self.size.height
+                && origin.y + size.height > self.origin.y
+        })
+    }
+
+    /// Returns the horizontal offset and the width of the part of a line with the given vertical
+    /// offset and height that is not covered by a floating element, see [`add_float`][].
+    ///
+    /// Floats that are placed in the left half of the area reduce the line from the left, and
+    /// floats in the right half reduce it from the right.
+    ///
+    /// [`add_float`]: #method.add_float
+    pub fn line_bounds(&self, y: Mm, height: Mm) -> (Mm, Mm) {
+        let mut left = Mm(0.0);
+        let mut right = self.size.width;
+        for (origin, size) in self.floats.borrow().iter() {
+            let x = origin.x - self.origin.x;
+            let top = origin.y - self.origin.y;
+            let overlaps = top < y + height
+                && top + size.height > y
+                && x < self.size.width
+                && x + size.width > Mm(0.0);
+            if !overlaps {
+                continue;
+            }
+            if x + size.width / 2.0 <= self.size.width / 2.0 {
+                left = left.max(x + size.width);
+            } else {
+                right = right.min(x);
+            }
+        }
+        (left, (right - left).max(Mm(0.0)))
+    }
+
+    /// Calls the given function with a copy of this area that is restricted to the given size and
+    /// clips everything drawn by the function to that size.
+    ///
+    /// Content that is drawn outside of the clipped area is not visible in the document.  For a
+    /// measuring area, the function is called with a resized copy of this area.
+    pub fn with_clip<F, R>(&self, size: impl Into<Size>, f: F) -> R
+    where
+        F: FnOnce(Area<'p>) -> R,
+    {
+        let mut area = self.clone();
+        area.set_size(size);
+        let bottom_right = self.position(Position::new(area.size.width, area.size.height));
+        let top_left = self.position(Position::default());
+        self.with_graphics_state(area, |layer| layer.add_clip(top_left, bottom_right), f)
+    }
+
+    /// Calls the given function with a copy of this area and draws everything drawn by the
+    /// function with the given opacity.
+    ///
+    /// The opacity is clamped to the range from 0.0 (invisible) to 1.0 (opaque).  It is applied
+    /// with an extended graphics state that sets the alpha constants for filling and stroking.
+    pub fn with_opacity<F, R>(&self, opacity: f32, f: F) -> R
+    where
+        F: FnOnce(Area<'p>) -> R,
+    {
+        let opacity = opacity.clamp(0.0, 1.0);
+        self.with_graphics_state(self.clone(), |layer| layer.set_opacity(opacity), f)
+    }
+
+    /// Calls the given function with a copy of this area and rotates and scales everything drawn
+    /// by the function around the given center.
+    ///
+    /// The center is relative to the upper left corner of the area, and the rotation is
+    /// clockwise.  The function draws in the untransformed coordinates of the area, so the size of
+    /// the area is not changed.
+    pub fn with_transform<F, R>(
+        &self,
+        center: Position,
+        rotation: impl Into<Rotation>,
+        scale: f32,
+        f: F,
+    ) -> R
+    where
+        F: FnOnce(Area<'p>) -> R,
+    {
+        let rotation = rotation.into();
+        let center = self.position(center);
+        self.with_graphics_state(
+            self.clone(),
+            |layer| layer.add_transform(center, rotation, scale),
+            f,
+        )
+    }
+
+    /// Saves the graphics state, applies the given changes to it and calls the given function
+    /// with the given area before the graphics state is restored.
+    ///
+    /// For a measuring area, the function is called without changing the graphics state.
+    fn with_graphics_state<S, F, R>(&self, area: Area<'p>, setup: S, f: F) -> R
+    where
+        S: FnOnce(&Layer<'p>),
+        F: FnOnce(Area<'p>) -> R,
+    {
+        if let Some(layer) = &self.layer {
+            let state = layer.save_state();
+            setup(layer);
+            let result = f(area);
+            layer.restore_state(state);
+            result
+        } else {
+            f(area)
         }
     }
 
@@ -576,6 +1361,40 @@ impl<'p> Area<'p> {
         self.size.height -= offset.y;
     }
 
+    /// Moves the top of this area down by the given height of the content that has been rendered
+    /// into it, reducing the drawable area.
+    ///
+    /// Layouts should use this method instead of [`add_offset`][] to place an element below the
+    /// previous elements, so that the element knows how much space it would have on an empty page,
+    /// see [`empty_height`][] and [`is_at_top`][].
+    ///
+    /// [`add_offset`]: #method.add_offset
+    /// [`empty_height`]: #method.empty_height
+    /// [`is_at_top`]: #method.is_at_top
+    pub fn add_content_offset(&mut self, height: Mm) {
+        self.add_offset(Position::new(0, height));
+        self.used_height += height;
+    }
+
+    /// Returns the height that this area would have if no content had been rendered above it on
+    /// the current page, i. e. the height that is available to an element on an empty page.
+    ///
+    /// In contrast to [`Context::content_height`][], this height does not include the space that
+    /// is used by the paddings, frames or table headers around the element.
+    ///
+    /// [`Context::content_height`]: ../struct.Context.html#method.content_height
+    pub fn empty_height(&self) -> Mm {
+        self.size.height + self.used_height
+    }
+
+    /// Returns whether no content has been rendered above this area on the current page, see
+    /// [`add_content_offset`][].
+    ///
+    /// [`add_content_offset`]: #method.add_content_offset
+    pub fn is_at_top(&self) -> bool {
+        self.used_height <= Mm(0.0)
+    }
+
     /// Sets the size of this area.
     pub fn set_size(&mut self, size: impl Into<Size>) {
         self.size = size.into();
@@ -631,8 +1450,9 @@ impl<'p> Area<'p> {
         rotation: Rotation,
         dpi: Option<f32>,
     ) {
-        self.layer
-            .add_image(image, self.position(position), scale, rotation, dpi);
+        if let Some(layer) = &self.layer {
+            layer.add_image(image, self.position(position), scale, rotation, dpi);
+        }
     }
 
     /// Draws a line with the given points and the given line style.
@@ -642,11 +1462,12 @@ impl<'p> Area<'p> {
     where
         I: IntoIterator<Item = Position>,
     {
-        self.layer.set_outline_thickness(line_style.thickness());
-        self.layer.set_outline_color(line_style.color());
-        self.layer.set_line_dash_pattern(line_style.dash(), line_style.gap(), line_style.dash2(), line_style.gap2());                   
-        self.layer
-            .add_line_shape(points.into_iter().map(|pos| self.position(pos)));
+        if let Some(layer) = &self.layer {
+            layer.set_outline_thickness(line_style.thickness());
+            layer.set_outline_color(line_style.color());
+            layer.set_line_dash_pattern(line_style.dash(), line_style.gap(), line_style.dash2(), line_style.gap2());
+            layer.add_line_shape(points.into_iter().map(|pos| self.position(pos)));
+        }
     }
     
     
@@ -656,9 +1477,10 @@ impl<'p> Area<'p> {
     pub fn draw_background<I>(&self, points: I, color: Color)
     where
         I: IntoIterator<Item = Position>,
-    {                         
-        self.layer
-            .add_poligon_shape(points.into_iter().map(|pos| self.position(pos)), Some(color));
+    {
+        if let Some(layer) = &self.layer {
+            layer.add_poligon_shape(points.into_iter().map(|pos| self.position(pos)), Some(color));
+        }
     }
     
     /// Tries to draw the given string at the given position and returns `true` if the area was
@@ -683,6 +1505,65 @@ impl<'p> Area<'p> {
         }
     }
 
+    /// Adds a link annotation with the given size at the given position to this area.
+    ///
+    /// The position is relative to the upper left corner of the area.  Clicking on the annotated
+    /// rectangle opens the link target.
+    pub fn add_link(&self, position: Position, size: impl Into<Size>, link: &Link) {
+        if let Some(layer) = &self.layer {
+            layer.add_link(self.position(position), size.into(), link);
+        }
+    }
+
+    /// Adds a link annotation for the given registered link with the given size at the given
+    /// position to this area.
+    ///
+    /// Links that have not been registered with the renderer of this area are ignored, see
+    /// [`check_link`][].
+    ///
+    /// [`check_link`]: #method.check_link
+    fn add_link_id(&self, position: Position, size: Size, link: LinkId) {
+        if let Some(layer) = &self.layer {
+            if let Some(link) = layer.page.links.borrow().get(link) {
+                layer.add_link(self.position(position), size, link);
+            }
+        }
+    }
+
+    /// Returns an error if the given link has not been registered with the renderer of this area.
+    fn check_link(&self, link: LinkId) -> Result<(), Error> {
+        match &self.layer {
+            Some(layer) if layer.page.links.borrow().get(link).is_none() => Err(Error::new(
+                "The link has not been registered for this document",
+                ErrorKind::InvalidData,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds a named destination at the upper edge of this area that can be used as the target of
+    /// a [`Link::Destination`][].
+    ///
+    /// If the same name is used for several destinations, the first one is used.
+    ///
+    /// [`Link::Destination`]: ../style/enum.Link.html#variant.Destination
+    pub fn add_destination(&self, name: impl AsRef<str>) {
+        if let Some(layer) = &self.layer {
+            layer.add_destination(name.as_ref(), self.position(Position::default()));
+        }
+    }
+
+    /// Adds a bookmark with the given title and level at the upper edge of this area to the
+    /// outline of the document.
+    ///
+    /// The bookmarks are nested according to their level:  A bookmark is added as a child of the
+    /// last preceding bookmark with a lower level.
+    pub fn add_bookmark(&self, title: impl AsRef<str>, level: usize) {
+        if let Some(layer) = &self.layer {
+            layer.add_bookmark(title.as_ref(), level, self.position(Position::default()));
+        }
+    }
+
     /// Creates a new text section at the given position if the text section fits in this area.
     ///
     /// The given style is only used to calculate the line height of the section.  The position is
@@ -706,12 +1587,26 @@ impl<'p> Area<'p> {
 }
 
 /// A text section that is drawn on an area of a PDF layer.
+///
+/// If a string is printed with a [`LinkId`][] in its style, a link annotation is added for the
+/// printed text.  Consecutive strings with the same link on the same line share one annotation.
+///
+/// [`LinkId`]: ../style/struct.LinkId.html
 pub struct TextSection<'f, 'p> {
     font_cache: &'f fonts::FontCache,
     area: Area<'p>,
     is_first: bool,
     metrics: fonts::Metrics,
     font: Option<(printpdf::IndirectFontRef, u8)>,
+    // The horizontal offset of the cursor on the current line
+    x: Mm,
+    // The link of the last string and its start offset on the current line
+    link: Option<(LinkId, Mm)>,
+    // The distance that the text cursor of the PDF layer is ahead of the offset x, caused by shaped
+    // glyphs with an advance that differs from their width in the PDF font
+    cursor_offset: Mm,
+    // The additional space after every space character
+    word_spacing: Mm,
 }
 
 impl<'f, 'p> TextSection<'f, 'p> {
@@ -724,8 +1619,10 @@ impl<'f, 'p> TextSection<'f, 'p> {
             return None;
         }
 
-        area.layer.begin_text_section();
-        area.layer.set_line_height(metrics.line_height);
+        if let Some(layer) = &area.layer {
+            layer.begin_text_section();
+            layer.set_line_height(metrics.line_height);
+        }
 
         Some(TextSection {
             font_cache,
@@ -733,14 +1630,31 @@ impl<'f, 'p> TextSection<'f, 'p> {
             is_first: true,
             metrics,
             font: None,
+            x: Mm(0.0),
+            link: None,
+            cursor_offset: Mm(0.0),
+            word_spacing: Mm(0.0),
         })
     }
 
+    /// Adds the link annotation for the last linked strings on the current line, if any.
+    fn finish_link(&mut self) {
+        if let Some((link, start)) = self.link.take() {
+            self.area.add_link_id(
+                Position::new(start, 0),
+                Size::new(self.x - start, self.metrics.line_height),
+                link,
+            );
+        }
+    }
+
     fn set_text_cursor(&self, x_offset: Mm) {
-        let cursor = self
-            .area
-            .position(Position::new(x_offset, self.metrics.ascent));
-        self.area.layer.set_text_cursor(cursor);
+        if let Some(layer) = &self.area.layer {
+            let cursor = self
+                .area
+                .position(Position::new(x_offset, self.metrics.ascent));
+            layer.set_text_cursor(cursor);
+        }
     }
 
     fn set_font(&mut self, font: &printpdf::IndirectFontRef, font_size: u8) {
@@ -752,7 +1666,9 @@ impl<'f, 'p> TextSection<'f, 'p> {
             .unwrap_or_default();
         if !font_is_set {
             self.font = Some((font.clone(), font_size));
-            self.area.layer.set_font(font, font_size);
+            if let Some(layer) = &self.area.layer {
+                layer.set_font(font, font_size);
+            }
         }
     }
 
@@ -763,22 +1679,63 @@ impl<'f, 'p> TextSection<'f, 'p> {
         if self.metrics.line_height > self.area.size.height {
             false
         } else {
-            self.area.layer.add_line_break();
+            self.finish_link();
+            if let Some(layer) = &self.area.layer {
+                layer.add_line_break();
+            }
             self.area.add_offset((0, self.metrics.line_height));
+            self.x = Mm(0.0);
+            self.cursor_offset = Mm(0.0);
             true
         }
     }
 
+    /// Sets the additional space that is added after every space character (U+0020) printed in
+    /// this text section, for example to justify a line.
+    pub fn set_word_spacing(&mut self, word_spacing: Mm) {
+        self.word_spacing = word_spacing;
+    }
+
     /// Prints the given string with the given style.
     ///
-    /// The font cache for this text section must contain the PDF font for the given style.
+    /// The font cache for this text section must contain the PDF font for the given style.  If the
+    /// font cache has fallback font families, characters that are not supported by the font for
+    /// the given style are printed with a fallback font (see [`Style::font_runs`][]).
+    ///
+    /// [`Style::font_runs`]: ../style/struct.Style.html#method.font_runs
     pub fn print_str(&mut self, s: impl AsRef<str>, style: Style) -> Result<(), Error> {
-        let font = style.font(self.font_cache);
         let s = s.as_ref();
+        self.advance(s, s.chars().next(), style)?;
+        for (font, s) in style.font_runs(self.font_cache, s) {
+            self.print_run(font, s, style, Direction::Auto)?;
+        }
+        Ok(())
+    }
+
+    /// Prints the given right-to-left string with the given style.
+    ///
+    /// The string must be in logical order, i. e. its first character is printed on the right.
+    /// Mirrored characters like parentheses are replaced with their counterpart.  Otherwise, this
+    /// method works like [`print_str`][].
+    ///
+    /// [`print_str`]: #method.print_str
+    pub fn print_rtl_str(&mut self, s: impl AsRef<str>, style: Style) -> Result<(), Error> {
+        let s = s.as_ref();
+        self.advance(s, s.chars().last(), style)?;
+        for (font, s) in style.font_runs(self.font_cache, s).into_iter().rev() {
+            self.print_run(font, s, style, Direction::RightToLeft)?;
+        }
+        Ok(())
+    }
 
+    /// Updates the cursor and the current link before printing the given string with the given
+    /// style, where `first_c` is the first character that is printed.
+    ///
+    /// Returns an error if the link of the style has not been registered for this document.
+    fn advance(&mut self, s: &str, first_c: Option<char>, style: Style) -> Result<(), Error> {
         // Adjust cursor to remove left bearing of the first character of the first string
         if self.is_first {
-            let x_offset = if let Some(first_c) = s.chars().next() {
+            let x_offset = if let Some(first_c) = first_c {
                 style.char_left_side_bearing(self.font_cache, first_c) * -1.0
             } else {
                 Mm(0.0)
@@ -787,12 +1744,46 @@ impl<'f, 'p> TextSection<'f, 'p> {
         }
         self.is_first = false;
 
-        let positions = font
+        if self.link.map(|(link, _)| link) != style.link() {
+            if let Some(link) = style.link() {
+                self.area.check_link(link)?;
+            }
+            self.finish_link();
+            self.link = style.link().map(|link| (link, self.x));
+        }
+        self.x += style.str_width(self.font_cache, s);
+        self.x += self.word_spacing * s.matches(' ').count() as f32;
+        Ok(())
+    }
+
+    fn print_run(
+        &mut self,
+        font: fonts::Font,
+        s: &str,
+        style: Style,
+        direction: Direction,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "shaping")]
+        {
+            if let Some(glyphs) = font.shape(self.font_cache, s, direction) {
+                return self.print_glyphs(font, s, &glyphs, style);
+            }
+        }
+
+        let reversed: String;
+        let s = if direction == Direction::RightToLeft {
+            reversed = reverse_rtl(s);
+            &reversed
+        } else {
+            s
+        };
+
+        let mut positions: Vec<_> = font
             .kerning(self.font_cache, s.chars())
             .into_iter()
             // Kerning is measured in 1/1000 em
             .map(|pos| pos * -1000.0)
-            .map(|pos| pos as i64);
+            .collect();
         let codepoints = if font.is_builtin() {
             // Built-in fonts always use the Windows-1252 encoding
             encode_win1252(s)?
@@ -800,26 +1791,170 @@ impl<'f, 'p> TextSection<'f, 'p> {
             font.glyph_ids(self.font_cache, s.chars())
         };
 
+        if codepoints.is_empty() {
+            // Don’t embed the font if we don’t print any glyphs with it
+            return Ok(());
+        }
+        let layer = if let Some(layer) = self.prepare_layer(font, style)? {
+            layer
+        } else {
+            return Ok(());
+        };
+        if let Some(first) = positions.first_mut() {
+            *first += self.take_cursor_offset(style) * 1000.0;
+        }
+        if self.word_spacing != Mm(0.0) {
+            let word_spacing = self.word_spacing_em(style);
+            for (idx, c) in s.chars().enumerate() {
+                if c == ' ' {
+                    if let Some(pos) = positions.get_mut(idx + 1) {
+                        *pos -= word_spacing * 1000.0;
+                    } else {
+                        self.cursor_offset -= self.word_spacing;
+                    }
+                }
+            }
+        }
+        layer.write_positioned_codepoints(positions.into_iter().map(|pos| pos as i64), codepoints);
+        Ok(())
+    }
+
+    /// Prints the given glyphs that have been shaped with the given font.
+    ///
+    /// `printpdf` moves the text cursor by the width of the glyph in the PDF font after every
+    /// glyph, so we have to adjust the position of every glyph to match the shaped advance and
+    /// offset.  Vertical offsets are applied by changing the text rise.  `s` is the shaped string
+    /// that the clusters of the glyphs refer to.
+    #[cfg(feature = "shaping")]
+    fn print_glyphs(
+        &mut self,
+        font: fonts::Font,
+        s: &str,
+        glyphs: &[fonts::ShapedGlyph],
+        style: Style,
+    ) -> Result<(), Error> {
+        if glyphs.is_empty() {
+            return Ok(());
+        }
+        let layer = if let Some(layer) = self.prepare_layer(font, style)? {
+            layer
+        } else {
+            return Ok(());
+        };
+
+        // The distance in em that the text cursor is ahead of the origin of the next glyph
+        let mut correction = self.take_cursor_offset(style);
+        let word_spacing = self.word_spacing_em(style);
+        let mut positioned = Vec::with_capacity(glyphs.len());
+        for glyph in glyphs {
+            let position = ((correction - glyph.x_offset) * 1000.0).round() as i64;
+            positioned.push((position, glyph.glyph_id, glyph.y_offset));
+            correction = font.pdf_glyph_width(self.font_cache, glyph.glyph_id) - glyph.x_advance
+                + glyph.x_offset;
+            if s[glyph.cluster..].starts_with(' ') {
+                correction -= word_spacing;
+            }
+        }
+        let font_size = f32::from(style.font_size());
+        self.cursor_offset = Mm::from(printpdf::Pt(correction * font_size));
+
+        // Glyphs with the same vertical offset are written together.
+        let mut rise = 0.0;
+        let mut start = 0;
+        while start < positioned.len() {
+            let y_offset = positioned[start].2;
+            let end = positioned[start..]
+                .iter()
+                .position(|(_, _, y)| *y != y_offset)
+                .map(|len| start + len)
+                .unwrap_or(positioned.len());
+            if y_offset != rise {
+                layer.set_text_rise(Mm::from(printpdf::Pt(y_offset * font_size)));
+                rise = y_offset;
+            }
+            let group = &positioned[start..end];
+            layer.write_positioned_codepoints(
+                group.iter().map(|(position, _, _)| *position),
+                group.iter().map(|(_, glyph_id, _)| *glyph_id),
+            );
+            start = end;
+        }
+        if rise != 0.0 {
+            layer.set_text_rise(Mm(0.0));
+        }
+        Ok(())
+    }
+
+    /// Embeds the given font if necessary and prepares the layer for printing with the given font
+    /// and style, returning the layer or `None` if this text section does not print to a layer.
+    fn prepare_layer(
+        &mut self,
+        font: fonts::Font,
+        style: Style,
+    ) -> Result<Option<Layer<'p>>, Error> {
+        let layer = if let Some(layer) = &self.area.layer {
+            layer.clone()
+        } else {
+            return Ok(None);
+        };
         let font = self
             .font_cache
-            .get_pdf_font(font)
+            .get_pdf_font(font)?
             .expect("Could not find PDF font in font cache");
-        self.area.layer.set_fill_color(style.color());
-        self.set_font(font, style.font_size());
+        layer.set_fill_color(style.color());
+        self.set_font(&font, style.font_size());
+        Ok(Some(layer))
+    }
 
-        self.area
-            .layer
-            .write_positioned_codepoints(positions, codepoints);
-        Ok(())
+    /// Returns the word spacing in em for the given style.
+    fn word_spacing_em(&self, style: Style) -> f32 {
+        printpdf::Pt::from(self.word_spacing).0 / f32::from(style.font_size())
+    }
+
+    /// Resets the cursor offset and returns it in em for the given style.
+    fn take_cursor_offset(&mut self, style: Style) -> f32 {
+        let offset = printpdf::Pt::from(self.cursor_offset).0 / f32::from(style.font_size());
+        self.cursor_offset = Mm(0.0);
+        offset
     }
 }
 
 impl<'f, 'p> Drop for TextSection<'f, 'p> {
     fn drop(&mut self) {
-        self.area.layer.end_text_section();
+        self.finish_link();
+        if let Some(layer) = &self.area.layer {
+            layer.end_text_section();
+        }
     }
 }
 
+/// Reverses the given right-to-left string so that it can be printed from left to right.
+///
+/// Non-spacing marks are kept after their base character, and paired characters are mirrored.
+fn reverse_rtl(s: &str) -> String {
+    let mut clusters: Vec<&str> = Vec::new();
+    let mut start = 0;
+    for (idx, c) in s.char_indices() {
+        if idx > 0 && unicode_bidi::bidi_class(c) != unicode_bidi::BidiClass::NSM {
+            clusters.push(&s[start..idx]);
+            start = idx;
+        }
+    }
+    clusters.push(&s[start..]);
+    clusters
+        .into_iter()
+        .rev()
+        .flat_map(str::chars)
+        .map(mirror)
+        .collect()
+}
+
+/// Returns the mirrored counterpart of the given character according to its Bidi_Mirroring_Glyph
+/// property if it has one, or the character itself.
+fn mirror(c: char) -> char {
+    unicode_bidi_mirroring::get_mirrored(c).unwrap_or(c)
+}
+
 /// Encodes the given string using the Windows-1252 encoding for use with built-in PDF fonts,
 /// returning an error if it contains unsupported characters.
 fn encode_win1252(s: &str) -> Result<Vec<u16>, Error> {
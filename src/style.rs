@@ -5,7 +5,7 @@
 //! a [`Cow<'_, str>`][] with a [`Style`][] annotation.
 //!
 //! A [`Style`][] is a combination of a [`FontFamily`][], a font size, a line spacing factor, a
-//! [`Color`][] and a combination of [`Effect`][]s (bold or italic).
+//! [`Color`][], a combination of [`Effect`][]s (bold or italic) and a [`LinkId`][].
 //!
 //! # Example
 //!
@@ -18,6 +18,7 @@
 //!
 //! [`Color`]: enum.Color.html
 //! [`Effect`]: enum.Effect.html
+//! [`LinkId`]: struct.LinkId.html
 //! [`FontFamily`]: ../fonts/struct.FontFamily.html
 //! [`Style`]: struct.Style.html
 //! [`StyledCow`]: struct.StyledCow.html
@@ -28,7 +29,9 @@
 //! [`Cow<'_, str>`]: https://doc.rust-lang.org/std/borrow/enum.Cow.html
 
 use std::borrow;
+use std::collections;
 use std::iter;
+use std::sync::atomic;
 
 use crate::fonts;
 use crate::Mm;
@@ -86,6 +89,130 @@ pub enum Effect {
     Italic,
 }
 
+/// The target of a link.
+///
+/// Elements that are wrapped in a [`LinkedElement`][] are clickable in the generated PDF document.
+/// A link either opens a URI or jumps to a named destination in the same document, see
+/// [`AnchoredElement`][].
+///
+/// To add a link to a part of a text, register the link target with [`Document::add_link`][] and
+/// set the returned [`LinkId`][] in the [`Style`][] of the text.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rckive_genpdf::{elements, fonts, style, Element as _};
+/// let font_family = fonts::from_files("./fonts", "LiberationSans", None)
+///     .expect("Failed to load font family");
+/// let mut doc = rckive_genpdf::Document::new(font_family);
+/// let issue = doc.add_link(style::Link::uri("https://example.com/issues/1"));
+/// let appendix = doc.add_link(style::Link::destination("appendix"));
+/// doc.push(
+///     elements::Paragraph::default()
+///         .string("See ")
+///         .styled_string("the issue", issue)
+///         .string(" and ")
+///         .styled_string("the appendix", appendix),
+/// );
+/// doc.push(elements::Text::new("Issues").linked(style::Link::uri("https://example.com/issues")));
+/// ```
+///
+/// [`Style`]: struct.Style.html
+/// [`LinkId`]: struct.LinkId.html
+/// [`Document::add_link`]: ../struct.Document.html#method.add_link
+/// [`LinkedElement`]: ../elements/struct.LinkedElement.html
+/// [`AnchoredElement`]: ../elements/struct.AnchoredElement.html
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Link {
+    /// A link to a URI, for example a web page.
+    Uri(String),
+    /// A link to a named destination in the same document.
+    Destination(String),
+}
+
+impl Link {
+    /// Creates a link to the given URI.
+    pub fn uri(uri: impl Into<String>) -> Link {
+        Link::Uri(uri.into())
+    }
+
+    /// Creates a link to the named destination with the given name.
+    pub fn destination(name: impl Into<String>) -> Link {
+        Link::Destination(name.into())
+    }
+}
+
+/// A reference to a [`Link`][] that has been registered with [`Document::add_link`][] or
+/// [`Renderer::add_link`][].
+///
+/// Text that is printed with a [`Style`][] that has a link is turned into a clickable link
+/// annotation.  A link reference may only be used for the document or renderer that returned it,
+/// otherwise printing the text fails.
+///
+/// [`Link`]: enum.Link.html
+/// [`Style`]: struct.Style.html
+/// [`Document::add_link`]: ../struct.Document.html#method.add_link
+/// [`Renderer::add_link`]: ../render/struct.Renderer.html#method.add_link
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LinkId {
+    registry: usize,
+    idx: usize,
+}
+
+/// The link targets that have been registered for a document, see [`LinkId`][].
+///
+/// Every registry has a unique ID that is stored in the link references it returns, so that
+/// references from other registries are detected.  Copies of a registry have the same ID.
+///
+/// [`LinkId`]: struct.LinkId.html
+#[derive(Clone, Debug)]
+pub(crate) struct LinkRegistry {
+    id: usize,
+    links: Vec<Link>,
+    ids: collections::HashMap<Link, LinkId>,
+}
+
+impl LinkRegistry {
+    pub(crate) fn new() -> LinkRegistry {
+        static NEXT_ID: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+        LinkRegistry {
+            id: NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed),
+            links: Vec::new(),
+            ids: collections::HashMap::new(),
+        }
+    }
+
+    /// Registers the given link target and returns a reference to it.  If the target has already
+    /// been registered, the existing reference is returned.
+    pub(crate) fn add(&mut self, link: Link) -> LinkId {
+        let registry = self.id;
+        let links = &mut self.links;
+        *self.ids.entry(link).or_insert_with_key(|link| {
+            links.push(link.clone());
+            LinkId {
+                registry,
+                idx: links.len() - 1,
+            }
+        })
+    }
+
+    /// Returns the link target for the given reference, or `None` if it has not been returned by
+    /// this registry.
+    pub(crate) fn get(&self, id: LinkId) -> Option<&Link> {
+        if id.registry == self.id {
+            self.links.get(id.idx)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for LinkRegistry {
+    fn default() -> LinkRegistry {
+        LinkRegistry::new()
+    }
+}
+
 /// A style annotation for a string.
 ///
 /// The annotation consists of:
@@ -94,12 +221,14 @@ pub enum Effect {
 /// - a line spacing factor, with 1 meaning single line spacing (defaults to 1)
 /// - an outline color, see [`Color`][] (defaults to black)
 /// - a combination of text effects, see [`Effect`][] (defaults to none)
+/// - a link target, see [`LinkId`][] (defaults to none)
 ///
 /// All properties are optional.  If they are not set, they can be inferred from parent styles or
 /// from the defaults.
 ///
 /// [`Color`]: enum.Color.html
 /// [`Effect`]: enum.Effect.html
+/// [`LinkId`]: struct.LinkId.html
 /// [`FontFamily`]: ../fonts/struct.FontFamily.html
 /// [`FontCache`]: ../fonts/struct.FontCache.html
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -111,6 +240,7 @@ pub struct Style {
     is_bold: bool,
     is_italic: bool,
     fit_font_size_to: Option<u8>,
+    link: Option<LinkId>,
 }
 
 impl Style {
@@ -143,6 +273,9 @@ impl Style {
         if let Some(fit_font_size_to) = style.fit_font_size_to {
             self.fit_font_size_to = Some(fit_font_size_to);
         }
+        if let Some(link) = style.link {
+            self.link = Some(link);
+        }
     }
 
     /// Combines this style and the given style and returns the result.
@@ -185,6 +318,11 @@ impl Style {
         self.line_spacing.unwrap_or(1.0)
     }
 
+    /// Returns the link target for this style, if set.
+    pub fn link(&self) -> Option<LinkId> {
+        self.link
+    }
+
     /// Sets the bold effect for this style.
     pub fn set_bold(&mut self) {
         self.is_bold = true;
@@ -262,6 +400,17 @@ impl Style {
         self
     }
 
+    /// Sets the link target for this style.
+    pub fn set_link(&mut self, link: LinkId) {
+        self.link = Some(link);
+    }
+
+    /// Sets the link target for this style and returns it.
+    pub fn with_link(mut self, link: LinkId) -> Self {
+        self.set_link(link);
+        self
+    }
+
     /// Calculates the width of the given character with this style using the data in the given
     /// font cache.
     ///
@@ -269,7 +418,7 @@ impl Style {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn char_width(&self, font_cache: &fonts::FontCache, c: char) -> Mm {
-        self.font(font_cache)
+        self.char_font(font_cache, c)
             .char_width(font_cache, c, self.font_size())
     }
 
@@ -280,7 +429,7 @@ impl Style {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn char_left_side_bearing(&self, font_cache: &fonts::FontCache, c: char) -> Mm {
-        self.font(font_cache)
+        self.char_font(font_cache, c)
             .char_left_side_bearing(font_cache, c, self.font_size())
     }
 
@@ -291,8 +440,10 @@ impl Style {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn str_width(&self, font_cache: &fonts::FontCache, s: &str) -> Mm {
-        let font = self.font(font_cache);
-        font.str_width(font_cache, s, self.font_size())
+        self.font_runs(font_cache, s)
+            .into_iter()
+            .map(|(font, s)| font.str_width(font_cache, s, self.font_size()))
+            .sum()
     }
 
     /// Returns the font family for this style or the default font family using the given font
@@ -315,6 +466,64 @@ impl Style {
         self.font_family(font_cache).get(*self)
     }
 
+    /// Returns the font that is used to print the given character with this style using the given
+    /// font cache.
+    ///
+    /// This is the font for this style if it has a glyph for the character.  Otherwise, it is the
+    /// font of the first fallback font family of the font cache that has a glyph for it (see
+    /// [`FontCache::add_fallback_font_family`][]).  If no fallback font has a glyph for the
+    /// character, the font for this style is used.
+    ///
+    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    /// [`FontCache::add_fallback_font_family`]: ../fonts/struct.FontCache.html#method.add_fallback_font_family
+    pub fn char_font(&self, font_cache: &fonts::FontCache, c: char) -> fonts::Font {
+        let font = self.font(font_cache);
+        if font.has_glyph(font_cache, c) {
+            return font;
+        }
+        font_cache
+            .fallback_font_families()
+            .iter()
+            .map(|family| family.get(*self))
+            .find(|font| font.has_glyph(font_cache, c))
+            .unwrap_or(font)
+    }
+
+    /// Splits the given string into runs of characters that are printed with the same font (see
+    /// [`char_font`][]) and returns the runs together with their font.
+    ///
+    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`char_font`]: #method.char_font
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    pub fn font_runs<'s>(
+        &self,
+        font_cache: &fonts::FontCache,
+        s: &'s str,
+    ) -> Vec<(fonts::Font, &'s str)> {
+        if font_cache.fallback_font_families().is_empty() {
+            return vec![(self.font(font_cache), s)];
+        }
+
+        let mut runs: Vec<(fonts::Font, &'s str)> = Vec::new();
+        let mut start = 0;
+        for (idx, c) in s.char_indices() {
+            let font = self.char_font(font_cache, c);
+            match runs.last_mut() {
+                Some((last_font, run)) if *last_font == font => {
+                    *run = &s[start..idx + c.len_utf8()];
+                }
+                _ => {
+                    start = idx;
+                    runs.push((font, &s[idx..idx + c.len_utf8()]));
+                }
+            }
+        }
+        runs
+    }
+
     /// Calculates the line height for strings with this style using the data in the given font
     /// cache.
     ///
@@ -343,6 +552,12 @@ impl From<Color> for Style {
     }
 }
 
+impl From<LinkId> for Style {
+    fn from(link: LinkId) -> Style {
+        Style::new().with_link(link)
+    }
+}
+
 impl From<Effect> for Style {
     fn from(effect: Effect) -> Style {
         let style = Style::new();
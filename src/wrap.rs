@@ -1,14 +1,22 @@
 //! Utilities for text wrapping.
 
 use std::mem;
+use std::vec;
 
 use crate::style;
 use crate::Context;
+use crate::LineBreaking;
 use crate::Mm;
 
 /// Combines a sequence of styled words into lines with a maximum width.
 ///
 /// If a word does not fit into a line, the wrapper tries to split it using the `split` function.
+///
+/// Per default, the lines are filled greedily.  With [`LineBreaking::Optimal`][], the lines are
+/// calculated for the whole sequence at once using [`break_optimal`][].
+///
+/// [`LineBreaking::Optimal`]: ../enum.LineBreaking.html#variant.Optimal
+/// [`break_optimal`]: fn.break_optimal.html
 pub struct Wrapper<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> {
     iter: I,
     context: &'c Context,
@@ -16,6 +24,7 @@ pub struct Wrapper<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> {
     x: Mm,
     buf: Vec<style::StyledCow<'s>>,
     has_overflowed: bool,
+    lines: Option<vec::IntoIter<(Vec<style::StyledCow<'s>>, usize)>>,
 }
 
 impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Wrapper<'c, 's, I> {
@@ -28,9 +37,39 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Wrapper<'c, 's, I> {
             x: Mm(0.0),
             buf: Vec::new(),
             has_overflowed: false,
+            lines: None,
         }
     }
 
+    /// Sets the line breaking algorithm for this wrapper and returns the wrapper.
+    ///
+    /// If the optimal line breaking is selected but no line breaks can be found so that all lines
+    /// fit into the maximum width, the wrapper falls back to greedy line breaking.
+    pub fn with_line_breaking(mut self, line_breaking: LineBreaking) -> Self
+    where
+        I: Clone,
+    {
+        self.lines = match line_breaking {
+            LineBreaking::Greedy => None,
+            LineBreaking::Optimal => {
+                let words: Vec<_> = self.iter.clone().collect();
+                break_optimal(&words, self.context, self.width).map(Vec::into_iter)
+            }
+        };
+        self
+    }
+
+    /// Sets the maximum width of the following lines.
+    ///
+    /// This can be used to wrap text with a different width per line, for example around a
+    /// floating element.  The width is ignored for optimal line breaking, see
+    /// [`with_line_breaking`][].
+    ///
+    /// [`with_line_breaking`]: #method.with_line_breaking
+    pub fn set_width(&mut self, width: Mm) {
+        self.width = width;
+    }
+
     /// Returns true if this wrapper has overflowed, i. e. if it encountered a word that it could
     /// not split so that it would fit into a line.
     pub fn has_overflowed(&self) -> bool {
@@ -44,6 +83,10 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Iterator for Wrapper<'c,
     type Item = (Vec<style::StyledCow<'s>>, usize);
 
     fn next(&mut self) -> Option<(Vec<style::StyledCow<'s>>, usize)> {
+        if let Some(lines) = &mut self.lines {
+            return lines.next();
+        }
+
         // Append words to self.buf until the maximum line length is reached
         for s in self.iter.by_ref() {
             let mut width = s.width(&self.context.font_cache);
@@ -114,6 +157,354 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Iterator for Wrapper<'c,
     }
 }
 
+/// A possible line break for [`break_optimal`][].
+///
+/// [`break_optimal`]: fn.break_optimal.html
+struct Breakpoint {
+    // The index of the word and the byte offset in the word that the break point is placed before
+    word: usize,
+    offset: usize,
+    // The width of the part of the word before and after the break point
+    before: Mm,
+    after: Mm,
+    // Whether the word is hyphenated at this break point
+    is_hyphen: bool,
+}
+
+/// A node of the shortest path through the break points for [`break_optimal`][].
+///
+/// [`break_optimal`]: fn.break_optimal.html
+#[derive(Clone, Copy)]
+struct Node {
+    demerits: f32,
+    previous: usize,
+}
+
+/// Breaks the given words into lines with the given maximum width using the total-fit algorithm
+/// by Knuth and Plass.
+///
+/// Instead of filling every line as much as possible, this function selects the line breaks that
+/// minimize the sum of the demerits of all lines.  The demerits of a line depend on how much the
+/// spaces of the line have to be stretched to fill the line, and on whether the line ends with a
+/// hyphen.  Words are hyphenated at the same positions as in [`Wrapper`][] if the `hyphenation`
+/// feature is enabled and a hyphenator is set.
+///
+/// The return value has the same format as the items of [`Wrapper`][].  If the words cannot be
+/// broken so that all lines fit into the given width, `None` is returned.
+///
+/// [`Wrapper`]: struct.Wrapper.html
+pub fn break_optimal<'s>(
+    words: &[style::StyledStr<'s>],
+    context: &Context,
+    width: Mm,
+) -> Option<Vec<(Vec<style::StyledCow<'s>>, usize)>> {
+    // The demerits of a line, see The TeXbook, chapter 14
+    const LINE_PENALTY: f32 = 10.0;
+    const HYPHEN_PENALTY: f32 = 50.0;
+    const DOUBLE_HYPHEN_DEMERITS: f32 = 3000.0;
+    const MAX_BADNESS: f32 = 10000.0;
+    // The stretchability of a space relative to its width
+    const SPACE_STRETCH: f32 = 0.5;
+
+    let font_cache = &context.font_cache;
+    let mark = "-";
+
+    // The accumulated widths of the words and of their trailing spaces
+    let mut widths = vec![Mm(0.0)];
+    let mut spaces = vec![Mm(0.0)];
+    let mut breakpoints = Vec::new();
+    for (idx, word) in words.iter().enumerate() {
+        let word_width = word.width(font_cache);
+        let trailing_spaces = &word.s[word.s.trim_end().len()..];
+        let space = word.style.str_width(font_cache, trailing_spaces);
+        breakpoints.push(Breakpoint {
+            word: idx,
+            offset: 0,
+            before: Mm(0.0),
+            after: word_width,
+            is_hyphen: false,
+        });
+        for offset in hyphenation_points(context, word.s) {
+            breakpoints.push(Breakpoint {
+                word: idx,
+                offset,
+                before: word.style.str_width(font_cache, &word.s[..offset]),
+                after: word.style.str_width(font_cache, &word.s[offset..]),
+                is_hyphen: true,
+            });
+        }
+        widths.push(widths[idx] + word_width);
+        spaces.push(spaces[idx] + space);
+    }
+    breakpoints.push(Breakpoint {
+        word: words.len(),
+        offset: 0,
+        before: Mm(0.0),
+        after: Mm(0.0),
+        is_hyphen: false,
+    });
+    if words.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let last = breakpoints.len() - 1;
+    let mut nodes: Vec<Option<Node>> = vec![None; breakpoints.len()];
+    nodes[0] = Some(Node {
+        demerits: 0.0,
+        previous: 0,
+    });
+    for end in 1..breakpoints.len() {
+        let b = &breakpoints[end];
+        let hyphen_width = if b.is_hyphen {
+            words[b.word].style.str_width(font_cache, mark)
+        } else {
+            Mm(0.0)
+        };
+        // The index of the last word that is (partly) contained in the line
+        let last_word = if b.offset == 0 { b.word - 1 } else { b.word };
+
+        for start in (0..end).rev() {
+            let a = &breakpoints[start];
+            let natural_width = if a.word == b.word {
+                let word = words[a.word];
+                let part = &word.s[a.offset..b.offset];
+                word.style.str_width(font_cache, part)
+            } else if b.offset == 0 {
+                let trailing = spaces[b.word] - spaces[b.word - 1];
+                a.after + widths[b.word] - widths[a.word + 1] - trailing
+            } else {
+                a.after + widths[b.word] - widths[a.word + 1] + b.before
+            } + hyphen_width;
+            if natural_width > width {
+                // Lines that start at earlier break points are even longer
+                break;
+            }
+            let node = if let Some(node) = nodes[start] {
+                node
+            } else {
+                continue;
+            };
+
+            let stretch = f32::from(spaces[last_word] - spaces[a.word]) * SPACE_STRETCH;
+            let shortfall = f32::from(width - natural_width);
+            let badness = if end == last {
+                // The last line does not have to be filled
+                0.0
+            } else if stretch > 0.0 {
+                (100.0 * (shortfall / stretch).powi(3)).min(MAX_BADNESS)
+            } else if shortfall > 0.0 {
+                MAX_BADNESS
+            } else {
+                0.0
+            };
+            let mut demerits = (LINE_PENALTY + badness).powi(2);
+            if b.is_hyphen {
+                demerits += HYPHEN_PENALTY.powi(2);
+                if a.is_hyphen {
+                    demerits += DOUBLE_HYPHEN_DEMERITS;
+                }
+            }
+
+            let demerits = node.demerits + demerits;
+            if nodes[end].map(|n| demerits < n.demerits).unwrap_or(true) {
+                nodes[end] = Some(Node {
+                    demerits,
+                    previous: start,
+                });
+            }
+        }
+    }
+
+    nodes[last]?;
+    let mut path = vec![last];
+    while let Some(&end) = path.last().filter(|&&end| end > 0) {
+        let node = nodes[end].expect("Missing node on the shortest path");
+        path.push(node.previous);
+    }
+    path.reverse();
+
+    let lines = path
+        .windows(2)
+        .map(|pair| {
+            let (a, b) = (&breakpoints[pair[0]], &breakpoints[pair[1]]);
+            let mut line = Vec::new();
+            for (idx, word) in words.iter().enumerate().take(b.word + 1).skip(a.word) {
+                let start = if idx == a.word { a.offset } else { 0 };
+                let end = if idx < b.word { word.s.len() } else { b.offset };
+                if start < end {
+                    line.push(style::StyledCow::new(&word.s[start..end], word.style));
+                }
+            }
+            if b.is_hyphen {
+                if let Some(part) = line.last_mut() {
+                    part.s.to_mut().push_str(mark);
+                }
+                (line, mark.len())
+            } else {
+                (line, 0)
+            }
+        })
+        .collect();
+    Some(lines)
+}
+
+/// Resolves the embedding levels of the given paragraph text according to the Unicode
+/// Bidirectional Algorithm, using the given base direction.
+///
+/// The returned vector contains the level of every byte of the text.  The levels only have to be
+/// resolved once per paragraph and can then be used to reorder every wrapped line of the
+/// paragraph with [`reorder`][].
+///
+/// [`reorder`]: fn.reorder.html
+pub fn resolve_levels(text: &str, rtl: bool) -> Vec<unicode_bidi::Level> {
+    unicode_bidi::BidiInfo::new(text, Some(base_level(rtl))).levels
+}
+
+/// Reorders the strings of a wrapped line for display according to the Unicode Bidirectional
+/// Algorithm, using the given base direction.
+///
+/// `levels` are the levels of the bytes of the line as returned by [`resolve_levels`][] for the
+/// paragraph.  Bytes at the end of the line that have been added while wrapping it, for example
+/// hyphens, get the level of the last byte with a level.  Only the rules L1 and L2 are applied to
+/// the line.
+///
+/// The strings are split into parts with a single direction.  The parts are returned in visual
+/// order from left to right, together with a flag that indicates whether a part is right-to-left
+/// text.  Right-to-left parts are still in logical order and have to be reversed when printing.
+///
+/// [`resolve_levels`]: fn.resolve_levels.html
+pub fn reorder<'a>(
+    line: &'a [style::StyledCow<'_>],
+    levels: &[unicode_bidi::Level],
+    rtl: bool,
+) -> Vec<(style::StyledStr<'a>, bool)> {
+    if !rtl && levels.iter().all(|level| level.is_ltr()) {
+        return line
+            .iter()
+            .map(|s| (style::StyledStr::new(s.s.as_ref(), s.style), false))
+            .collect();
+    }
+
+    let base = base_level(rtl);
+    let text: String = line.iter().map(|s| s.s.as_ref()).collect();
+    let last = levels.last().copied().unwrap_or(base);
+    let mut line_levels: Vec<_> = (0..text.len())
+        .map(|idx| levels.get(idx).copied().unwrap_or(last))
+        .collect();
+
+    // L1: Reset separators and trailing whitespace to the base level
+    let mut is_trailing = true;
+    for (idx, c) in text.char_indices().rev() {
+        use unicode_bidi::BidiClass::*;
+
+        let is_reset = match unicode_bidi::bidi_class(c) {
+            B | S => {
+                is_trailing = true;
+                true
+            }
+            WS | FSI | LRI | RLI | PDI | RLE | LRE | RLO | LRO | PDF | BN => is_trailing,
+            _ => {
+                is_trailing = false;
+                false
+            }
+        };
+        if is_reset {
+            for level in &mut line_levels[idx..idx + c.len_utf8()] {
+                *level = base;
+            }
+        }
+    }
+
+    // L2: Reverse the sequences of runs with at least the current level, from the highest level
+    // to the lowest odd level
+    let mut runs: Vec<(usize, usize, unicode_bidi::Level)> = Vec::new();
+    for (idx, level) in line_levels.into_iter().enumerate() {
+        match runs.last_mut() {
+            Some((_, end, run_level)) if *run_level == level => *end = idx + 1,
+            _ => runs.push((idx, idx + 1, level)),
+        }
+    }
+    let max_level = runs.iter().map(|run| run.2.number()).max().unwrap_or(0);
+    let min_level = runs.iter().map(|run| run.2.number()).min().unwrap_or(0);
+    let lowest_odd_level = min_level.max(1) | 1;
+    for level in (lowest_odd_level..=max_level).rev() {
+        let mut start = 0;
+        while start < runs.len() {
+            if runs[start].2.number() < level {
+                start += 1;
+                continue;
+            }
+            let end = runs[start..]
+                .iter()
+                .position(|run| run.2.number() < level)
+                .map(|len| start + len)
+                .unwrap_or(runs.len());
+            runs[start..end].reverse();
+            start = end;
+        }
+    }
+
+    let mut parts = Vec::new();
+    for (run_start, run_end, level) in runs {
+        let is_rtl = level.is_rtl();
+        let mut run_parts = Vec::new();
+        let mut start = 0;
+        for s in line {
+            let end = start + s.s.len();
+            let part_start = run_start.max(start);
+            let part_end = run_end.min(end);
+            if part_start < part_end {
+                let part = &s.s[part_start - start..part_end - start];
+                run_parts.push((style::StyledStr::new(part, s.style), is_rtl));
+            }
+            start = end;
+        }
+        if is_rtl {
+            run_parts.reverse();
+        }
+        parts.extend(run_parts);
+    }
+    parts
+}
+
+fn base_level(rtl: bool) -> unicode_bidi::Level {
+    if rtl {
+        unicode_bidi::Level::rtl()
+    } else {
+        unicode_bidi::Level::ltr()
+    }
+}
+
+/// Returns whether the given text has a right-to-left base direction, i. e. whether its first
+/// character with a strong direction is a right-to-left character.
+pub fn is_rtl<'s>(text: impl IntoIterator<Item = &'s str>) -> bool {
+    text.into_iter()
+        .flat_map(str::chars)
+        .find_map(|c| match unicode_bidi::bidi_class(c) {
+            unicode_bidi::BidiClass::L => Some(false),
+            unicode_bidi::BidiClass::R | unicode_bidi::BidiClass::AL => Some(true),
+            _ => None,
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(not(feature = "hyphenation"))]
+fn hyphenation_points(_context: &Context, _s: &str) -> Vec<usize> {
+    Vec::new()
+}
+
+/// Returns the byte offsets of the positions where the given string can be hyphenated.
+#[cfg(feature = "hyphenation")]
+fn hyphenation_points(context: &Context, s: &str) -> Vec<usize> {
+    use hyphenation::Hyphenator;
+
+    if let Some(hyphenator) = &context.hyphenator {
+        hyphenator.hyphenate(s).breaks
+    } else {
+        Vec::new()
+    }
+}
+
 #[cfg(not(feature = "hyphenation"))]
 fn split<'s>(
     _context: &Context,